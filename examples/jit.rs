@@ -1,32 +1,31 @@
-extern crate llvm_sys;
 #[macro_use]
 extern crate llvm;
 
 use std::mem;
 
-use llvm_sys::core::LLVMGetParam;
-
 fn main() {
-    let mut context = llvm::Context::new();
-    let mut module = context.create_module_with_name(llvmstr!("my module"));
-    let mut builder = context.create_builder();
+    let context = llvm::Context::new();
+    let module = context.create_module_with_name(llvmstr!("my module"));
 
     let func_name = llvmstr!("add");
-    let func = module.add_function(
-        llvm::types::Function::new(
-            context.i64_type(),
-            &[context.i64_type(), context.i64_type()],
-            false,
-        ),
-        func_name,
-    );
-    let bb = context.append_basic_block(func, func_name);
-    builder.position_at_end(bb);
-
-    // get the function's arguments
-    unsafe {
-        let x = LLVMGetParam(func, 0);
-        let y = LLVMGetParam(func, 1);
+    {
+        // The function borrows the module, so it has to go out of scope
+        // before the module can be moved into the execution engine.
+        let mut builder = context.create_builder();
+        let func = module.add_function(
+            llvm::types::Function::new(
+                context.i64_type(),
+                &[context.i64_type(), context.i64_type()],
+                false,
+            ),
+            func_name,
+        );
+        let bb = context.append_basic_block(func, func_name);
+        builder.position_at_end(bb);
+
+        // get the function's arguments
+        let x = func.get_param(0).unwrap();
+        let y = func.get_param(1).unwrap();
 
         let s1 = builder.build_add(x, y, llvmstr!("s1"));
         builder.build_ret(s1);
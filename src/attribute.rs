@@ -0,0 +1,280 @@
+//! Attributes
+//!
+//! Unlike other names in this crate, attribute names, keys and values are
+//! passed as `&str` rather than `T: Borrow<Str>`, since the C API takes them
+//! along with their length, so they don't need to be null terminated.
+
+use std::fmt;
+use std::mem::transmute;
+use std::slice;
+use std::str;
+
+use libc::c_char;
+
+use super::*;
+
+/// Where an attribute applies: to the return value, to the function as a
+/// whole, or to a parameter. Parameters are numbered from 0, and LLVM can't
+/// represent `Param(u32::MAX)`, as it numbers them from 1 internally.
+///
+/// # Panics
+///
+/// Methods that take an `AttributeIndex` panic when given `Param(u32::MAX)`:
+///
+/// ```rust,should_panic
+/// #[macro_use] extern crate llvm;
+/// use llvm::AttributeIndex;
+/// # fn main() {
+/// # let context = llvm::Context::new();
+/// # let module = context.create_module_with_name(llvmstr!("my module"));
+/// let func_type = llvm::types::Function::new(context.void_type(), &[], false);
+/// let func = module.add_function(func_type, llvmstr!("f"));
+/// func.attribute_count(AttributeIndex::Param(u32::max_value()));
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AttributeIndex {
+    Return,
+    Function,
+    Param(u32),
+}
+
+impl From<AttributeIndex> for LLVMAttributeIndex {
+    fn from(index: AttributeIndex) -> LLVMAttributeIndex {
+        match index {
+            AttributeIndex::Return => LLVMAttributeReturnIndex,
+            AttributeIndex::Function => LLVMAttributeFunctionIndex,
+            AttributeIndex::Param(i) => i
+                .checked_add(1)
+                .expect("attribute parameter index out of range"),
+        }
+    }
+}
+
+/// An attribute of a function, return value, parameter or call site.
+///
+/// There are enum attributes, e.g. `nounwind`, which are either present or
+/// not; integer attributes, e.g. `align`, which are enum attributes with a
+/// value; and string attributes, e.g. `"target-cpu"="x86-64"`, which are key
+/// value pairs that LLVM passes through to its backends.
+///
+/// `&Attribute`s are created with `Context::create_enum_attribute` or
+/// `Context::create_string_attribute`, and are owned by the `Context`.
+///
+/// # Example
+///
+/// ```rust
+/// #[macro_use] extern crate llvm;
+/// use llvm::AttributeIndex;
+/// # fn main() {
+/// # let context = llvm::Context::new();
+/// # let module = context.create_module_with_name(llvmstr!("my module"));
+/// let ptr_type = context.i32_type().pointer();
+/// let func_type = llvm::types::Function::new(ptr_type, &[ptr_type], false);
+/// let func = module.add_function(func_type, llvmstr!("f"));
+///
+/// let nounwind = context.create_enum_attribute("nounwind", 0).unwrap();
+/// let align = context.create_enum_attribute("align", 16).unwrap();
+/// let cpu = context.create_string_attribute("target-cpu", "x86-64");
+///
+/// func.add_attribute(AttributeIndex::Function, nounwind);
+/// func.add_attribute(AttributeIndex::Function, cpu);
+/// func.add_attribute(AttributeIndex::Return, align);
+/// func.add_attribute(AttributeIndex::Param(0), align);
+///
+/// let attr = func.get_enum_attribute(AttributeIndex::Param(0), "align").unwrap();
+/// assert_eq!(attr.enum_value(), Some(16));
+/// let attr = func.get_string_attribute(AttributeIndex::Function, "target-cpu").unwrap();
+/// assert_eq!(attr.string_value(), Some("x86-64"));
+///
+/// func.remove_enum_attribute(AttributeIndex::Return, "align");
+/// assert_eq!(func.attribute_count(AttributeIndex::Return), 0);
+/// # }
+/// ```
+pub struct Attribute(LLVMOpaqueAttributeRef);
+
+impl<'a> From<&'a Attribute> for LLVMAttributeRef {
+    fn from(attr: &'a Attribute) -> LLVMAttributeRef {
+        unsafe { transmute::<&Attribute, LLVMAttributeRef>(attr) }
+    }
+}
+
+impl Attribute {
+    pub unsafe fn from_raw<'a>(ptr: LLVMAttributeRef) -> &'a Self {
+        transmute::<LLVMAttributeRef, &Self>(ptr)
+    }
+
+    /// Returns the kind of the enum attribute called `name`, or `None` if
+    /// LLVM doesn't know it. Kinds aren't stable across LLVM versions, so
+    /// attributes should be referred to by name where possible.
+    pub fn kind_for_name(name: &str) -> Option<u32> {
+        enum_kind(name)
+    }
+
+    /// Returns true for enum and integer attributes.
+    pub fn is_enum(&self) -> bool {
+        unsafe { LLVMIsEnumAttribute(self.into()) == 1 }
+    }
+
+    pub fn is_string(&self) -> bool {
+        unsafe { LLVMIsStringAttribute(self.into()) == 1 }
+    }
+
+    /// Returns the kind of an enum attribute, or `None` for a string
+    /// attribute.
+    pub fn enum_kind(&self) -> Option<u32> {
+        if self.is_enum() {
+            unsafe { Some(LLVMGetEnumAttributeKind(self.into())) }
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value of an enum attribute, which is 0 unless it is an
+    /// integer attribute, or `None` for a string attribute.
+    pub fn enum_value(&self) -> Option<u64> {
+        if self.is_enum() {
+            unsafe { Some(LLVMGetEnumAttributeValue(self.into())) }
+        } else {
+            None
+        }
+    }
+
+    /// Returns the key of a string attribute, or `None` for an enum
+    /// attribute or a key that isn't valid UTF-8.
+    pub fn string_kind(&self) -> Option<&str> {
+        if self.is_string() {
+            unsafe { from_llvm_str(|len| LLVMGetStringAttributeKind(self.into(), len)) }
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value of a string attribute, or `None` for an enum
+    /// attribute or a value that isn't valid UTF-8.
+    pub fn string_value(&self) -> Option<&str> {
+        if self.is_string() {
+            unsafe { from_llvm_str(|len| LLVMGetStringAttributeValue(self.into(), len)) }
+        } else {
+            None
+        }
+    }
+}
+
+// Wraps the unterminated strings that LLVM returns along with their length.
+unsafe fn from_llvm_str<'a, F>(f: F) -> Option<&'a str>
+where
+    F: FnOnce(*mut u32) -> *const c_char,
+{
+    let mut len = 0;
+    let ptr = f(&mut len);
+    str::from_utf8(slice::from_raw_parts(ptr as *const u8, len as usize)).ok()
+}
+
+impl fmt::Debug for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.enum_kind(), self.enum_value()) {
+            (Some(kind), Some(val)) => write!(f, "llvm::Attribute({}={})", kind, val),
+            _ => write!(
+                f,
+                "llvm::Attribute({:?}={:?})",
+                self.string_kind().unwrap_or(""),
+                self.string_value().unwrap_or("")
+            ),
+        }
+    }
+}
+
+impl PartialEq for Attribute {
+    fn eq(&self, other: &Attribute) -> bool {
+        LLVMAttributeRef::from(self) == LLVMAttributeRef::from(other)
+    }
+}
+
+impl Eq for Attribute {}
+
+// Returns the kind of the enum attribute called `name`, e.g. `nounwind`, or
+// `None` if LLVM doesn't know it.
+pub(crate) fn enum_kind(name: &str) -> Option<u32> {
+    match unsafe { LLVMGetEnumAttributeKindForName(name.as_ptr() as *const _, name.len()) } {
+        0 => None,
+        kind => Some(kind),
+    }
+}
+
+// Generates the methods for adding, getting and removing the attributes of
+// `$ty`, using the given C API functions.
+macro_rules! attribute_fns {
+    ($ty:ty,
+     $add:ident, $count:ident, $get_all:ident,
+     $get_enum:ident, $get_string:ident,
+     $remove_enum:ident, $remove_string:ident) => {
+        impl $ty {
+            pub fn add_attribute(&self, index: AttributeIndex, attr: &Attribute) {
+                unsafe { $add(self.into(), index.into(), attr.into()) }
+            }
+
+            pub fn attribute_count(&self, index: AttributeIndex) -> u32 {
+                unsafe { $count(self.into(), index.into()) }
+            }
+
+            /// Returns all of the attributes at `index`.
+            pub fn attributes(&self, index: AttributeIndex) -> Vec<&Attribute> {
+                let count = self.attribute_count(index) as usize;
+                let mut attrs = vec![::std::ptr::null_mut(); count];
+                unsafe {
+                    $get_all(self.into(), index.into(), attrs.as_mut_ptr());
+                    attrs.into_iter().map(|attr| Attribute::from_raw(attr)).collect()
+                }
+            }
+
+            /// Returns the enum attribute called `name` at `index`, or `None`
+            /// if it isn't present.
+            pub fn get_enum_attribute(
+                &self,
+                index: AttributeIndex,
+                name: &str,
+            ) -> Option<&Attribute> {
+                let kind = $crate::attribute::enum_kind(name)?;
+                let attr = unsafe { $get_enum(self.into(), index.into(), kind) };
+
+                if attr.is_null() {
+                    None
+                } else {
+                    unsafe { Some(Attribute::from_raw(attr)) }
+                }
+            }
+
+            /// Returns the string attribute with the key `key` at `index`, or
+            /// `None` if it isn't present.
+            pub fn get_string_attribute(
+                &self,
+                index: AttributeIndex,
+                key: &str,
+            ) -> Option<&Attribute> {
+                let (ptr, len) = (key.as_ptr() as *const _, key.len() as u32);
+                let attr = unsafe { $get_string(self.into(), index.into(), ptr, len) };
+
+                if attr.is_null() {
+                    None
+                } else {
+                    unsafe { Some(Attribute::from_raw(attr)) }
+                }
+            }
+
+            /// Removes the enum attribute called `name` at `index`, if present.
+            pub fn remove_enum_attribute(&self, index: AttributeIndex, name: &str) {
+                if let Some(kind) = $crate::attribute::enum_kind(name) {
+                    unsafe { $remove_enum(self.into(), index.into(), kind) }
+                }
+            }
+
+            /// Removes the string attribute with the key `key` at `index`, if
+            /// present.
+            pub fn remove_string_attribute(&self, index: AttributeIndex, key: &str) {
+                let (ptr, len) = (key.as_ptr() as *const _, key.len() as u32);
+                unsafe { $remove_string(self.into(), index.into(), ptr, len) }
+            }
+        }
+    };
+}
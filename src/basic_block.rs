@@ -0,0 +1,365 @@
+//! Basic blocks
+
+use std::marker::PhantomData;
+
+use super::*;
+
+/// A basic block: a sequence of instructions ending with a terminator.
+///
+/// `&BasicBlock`s are created with `Context::append_basic_block` or
+/// `BasicBlock::insert_before`, and are owned by the function they belong to.
+pub struct BasicBlock(Value);
+value_subclass!(BasicBlock, Value);
+
+impl<'a> From<&'a BasicBlock> for LLVMBasicBlockRef {
+    fn from(block: &'a BasicBlock) -> LLVMBasicBlockRef {
+        unsafe { LLVMValueAsBasicBlock(block.into()) }
+    }
+}
+
+impl BasicBlock {
+    /// Like `from_raw`, but from the `LLVMBasicBlockRef` used by the parts of
+    /// the C API that deal with basic blocks rather than values.
+    pub unsafe fn from_raw_block<'a>(ptr: LLVMBasicBlockRef) -> &'a Self {
+        Self::from_raw(LLVMBasicBlockAsValue(ptr))
+    }
+
+    /// Returns the function this block belongs to, or `None` if it has been
+    /// removed from its function.
+    pub fn parent(&self) -> Option<&Function> {
+        let func = unsafe { LLVMGetBasicBlockParent(self.into()) };
+
+        if func.is_null() {
+            None
+        } else {
+            unsafe { Some(Function::from_raw(func)) }
+        }
+    }
+
+    /// Returns the terminator instruction of this block, or `None` if the
+    /// block is not (yet) well formed.
+    pub fn terminator(&self) -> Option<&Instruction> {
+        let term = unsafe { LLVMGetBasicBlockTerminator(self.into()) };
+
+        if term.is_null() {
+            None
+        } else {
+            unsafe { Some(Instruction::from_raw(term)) }
+        }
+    }
+
+    pub fn first_instruction(&self) -> Option<&Instruction> {
+        let inst = unsafe { LLVMGetFirstInstruction(self.into()) };
+
+        if inst.is_null() {
+            None
+        } else {
+            unsafe { Some(Instruction::from_raw(inst)) }
+        }
+    }
+
+    pub fn last_instruction(&self) -> Option<&Instruction> {
+        let inst = unsafe { LLVMGetLastInstruction(self.into()) };
+
+        if inst.is_null() {
+            None
+        } else {
+            unsafe { Some(Instruction::from_raw(inst)) }
+        }
+    }
+
+    /// Returns an iterator over the instructions of this block. Use `rev()`
+    /// to iterate from the terminator backwards.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i32_type = context.i32_type();
+    /// let func_type = llvm::types::Function::new(i32_type, &[], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let entry = context.append_basic_block(func, llvmstr!("entry"));
+    ///
+    /// let mut builder = context.create_builder();
+    /// builder.position_at_end(entry);
+    /// let a = builder.build_alloca(i32_type, llvmstr!("a"));
+    /// let b = builder.build_alloca(i32_type, llvmstr!("b"));
+    /// let c = builder.build_alloca(i32_type, llvmstr!("c"));
+    /// let ret = builder.build_ret(i32_type.constant(0)).try_as_instruction().unwrap();
+    ///
+    /// assert_eq!(entry.instructions().collect::<Vec<_>>(), vec![a, b, c, ret]);
+    /// assert_eq!(entry.instructions().rev().collect::<Vec<_>>(), vec![ret, c, b, a]);
+    ///
+    /// // Both ends can be taken from, and stop once they meet in the middle.
+    /// let mut iter = entry.instructions();
+    /// assert_eq!(iter.next(), Some(a));
+    /// assert_eq!(iter.next_back(), Some(ret));
+    /// assert_eq!(iter.next_back(), Some(c));
+    /// assert_eq!(iter.next(), Some(b));
+    /// assert_eq!(iter.next(), None);
+    /// assert_eq!(iter.next_back(), None);
+    ///
+    /// let empty = context.append_basic_block(func, llvmstr!("empty"));
+    /// assert_eq!(empty.instructions().count(), 0);
+    /// # }
+    /// ```
+    pub fn instructions(&self) -> InstructionIter {
+        InstructionIter {
+            front: self.first_instruction(),
+            back: self.last_instruction(),
+        }
+    }
+
+    /// Returns an iterator over the blocks that the terminator of this block
+    /// can branch to.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let func_type = llvm::types::Function::new(context.void_type(), &[context.i1_type()], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// let then = context.append_basic_block(func, llvmstr!("then"));
+    /// let join = context.append_basic_block(func, llvmstr!("join"));
+    ///
+    /// // Without a terminator there are no successors.
+    /// assert_eq!(entry.successors().count(), 0);
+    ///
+    /// let mut builder = context.create_builder();
+    /// builder.position_at_end(entry);
+    /// builder.build_cond_br(func.get_param(0).unwrap(), then, join);
+    /// builder.position_at_end(then);
+    /// builder.build_br(join);
+    /// builder.position_at_end(join);
+    /// builder.build_ret_void();
+    ///
+    /// assert_eq!(entry.successors().collect::<Vec<_>>(), vec![then, join]);
+    /// assert_eq!(then.successors().collect::<Vec<_>>(), vec![join]);
+    /// assert_eq!(join.successors().count(), 0);
+    /// # }
+    /// ```
+    pub fn successors(&self) -> SuccessorIter {
+        SuccessorIter {
+            term: unsafe { LLVMGetBasicBlockTerminator(self.into()) },
+            index: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the blocks whose terminators can branch to
+    /// this block. Like in the C++ API, a block is yielded once per edge, so
+    /// it may be yielded more than once, e.g. for a `switch`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i32_type = context.i32_type();
+    /// let func_type = llvm::types::Function::new(context.void_type(), &[i32_type], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// let other = context.append_basic_block(func, llvmstr!("other"));
+    /// let join = context.append_basic_block(func, llvmstr!("join"));
+    ///
+    /// let mut builder = context.create_builder();
+    /// builder.position_at_end(entry);
+    /// let switch = builder.build_switch(func.get_param(0).unwrap(), join, 1);
+    /// switch.add_case(i32_type.constant(1), join);
+    /// builder.position_at_end(other);
+    /// let br = builder.build_br(join);
+    /// builder.position_at_end(join);
+    /// builder.build_ret_void();
+    ///
+    /// let mut preds = join.predecessors().collect::<Vec<_>>();
+    /// preds.sort_by_key(|block| block.to_string());
+    /// assert_eq!(preds, vec![entry, entry, other]);
+    /// assert_eq!(entry.predecessors().count(), 0);
+    ///
+    /// // A terminator that has been removed from its block is no edge, but
+    /// // doesn't hide the edges after it either.
+    /// br.try_as_instruction().unwrap().remove_from_parent();
+    /// assert_eq!(join.predecessors().collect::<Vec<_>>(), vec![entry, entry]);
+    /// # }
+    /// ```
+    pub fn predecessors(&self) -> PredecessorIter {
+        PredecessorIter {
+            users: self.users(),
+        }
+    }
+
+    /// Creates a new basic block and inserts it immediately before this one.
+    ///
+    /// Specifying a name is optional; just pass an empty string
+    pub fn insert_before<T: Borrow<Str>>(&self, name: &T) -> &BasicBlock {
+        unsafe {
+            let context = LLVMGetTypeContext(LLVMTypeOf(self.into()));
+            BasicBlock::from_raw_block(LLVMInsertBasicBlockInContext(
+                context,
+                self.into(),
+                name.borrow().as_ptr(),
+            ))
+        }
+    }
+
+    /// Moves this block so that it immediately follows `pos`, which must be
+    /// in the same function.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let func_type = llvm::types::Function::new(context.void_type(), &[], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let a = context.append_basic_block(func, llvmstr!("a"));
+    /// let b = context.append_basic_block(func, llvmstr!("b"));
+    /// let c = context.append_basic_block(func, llvmstr!("c"));
+    ///
+    /// a.move_after(c);
+    /// assert_eq!(func.basic_blocks().collect::<Vec<_>>(), vec![b, c, a]);
+    /// b.move_before(a);
+    /// assert_eq!(func.basic_blocks().collect::<Vec<_>>(), vec![c, b, a]);
+    /// # }
+    /// ```
+    pub fn move_after(&self, pos: &BasicBlock) {
+        unsafe { LLVMMoveBasicBlockAfter(self.into(), pos.into()) }
+    }
+
+    /// Moves this block so that it immediately precedes `pos`, which must be
+    /// in the same function.
+    pub fn move_before(&self, pos: &BasicBlock) {
+        unsafe { LLVMMoveBasicBlockBefore(self.into(), pos.into()) }
+    }
+
+    /// Removes this block from its function without deleting it. The C API
+    /// provides no way to re-insert the block afterwards, or to delete a block
+    /// without a parent, so it can only be inspected and is leaked.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let func_type = llvm::types::Function::new(context.void_type(), &[], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// let dead = context.append_basic_block(func, llvmstr!("dead"));
+    ///
+    /// dead.remove_from_parent();
+    /// assert_eq!(dead.parent(), None);
+    /// assert_eq!(func.basic_blocks().collect::<Vec<_>>(), vec![entry]);
+    /// # }
+    /// ```
+    pub fn remove_from_parent(&self) {
+        unsafe { LLVMRemoveBasicBlockFromParent(self.into()) }
+    }
+
+    /// Removes this block from its function and deletes it. Marked as
+    /// `unsafe` because any remaining references to the block or its
+    /// instructions are left dangling, and because the block must still
+    /// belong to a function.
+    pub unsafe fn delete(&self) {
+        LLVMDeleteBasicBlock(self.into());
+    }
+}
+
+#[derive(Debug)]
+pub struct InstructionIter<'a> {
+    front: Option<&'a Instruction>,
+    back: Option<&'a Instruction>,
+}
+
+impl<'a> InstructionIter<'a> {
+    // Returns `inst` and ends the iteration if `front` and `back` have met.
+    fn finish_if_met(&mut self, inst: &'a Instruction) -> &'a Instruction {
+        if let (Some(front), Some(back)) = (self.front, self.back) {
+            if LLVMValueRef::from(front) == LLVMValueRef::from(back) {
+                self.front = None;
+                self.back = None;
+            }
+        }
+        inst
+    }
+}
+
+impl<'a> Iterator for InstructionIter<'a> {
+    type Item = &'a Instruction;
+
+    fn next(&mut self) -> Option<&'a Instruction> {
+        let inst = self.front?;
+        let inst = self.finish_if_met(inst);
+
+        if self.front.is_some() {
+            let next = unsafe { LLVMGetNextInstruction(inst.into()) };
+            self.front = unsafe { Some(Instruction::from_raw(next)) };
+        }
+        Some(inst)
+    }
+}
+
+impl<'a> DoubleEndedIterator for InstructionIter<'a> {
+    fn next_back(&mut self) -> Option<&'a Instruction> {
+        let inst = self.back?;
+        let inst = self.finish_if_met(inst);
+
+        if self.back.is_some() {
+            let prev = unsafe { LLVMGetPreviousInstruction(inst.into()) };
+            self.back = unsafe { Some(Instruction::from_raw(prev)) };
+        }
+        Some(inst)
+    }
+}
+
+#[derive(Debug)]
+pub struct SuccessorIter<'a> {
+    term: LLVMValueRef,
+    index: u32,
+    _marker: PhantomData<&'a BasicBlock>,
+}
+
+impl<'a> Iterator for SuccessorIter<'a> {
+    type Item = &'a BasicBlock;
+
+    fn next(&mut self) -> Option<&'a BasicBlock> {
+        if self.term.is_null() || self.index >= unsafe { LLVMGetNumSuccessors(self.term) } {
+            None
+        } else {
+            let block = unsafe { LLVMGetSuccessor(self.term, self.index) };
+            self.index += 1;
+            unsafe { Some(BasicBlock::from_raw_block(block)) }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PredecessorIter<'a> {
+    users: UserIter<'a>,
+}
+
+impl<'a> Iterator for PredecessorIter<'a> {
+    type Item = &'a BasicBlock;
+
+    fn next(&mut self) -> Option<&'a BasicBlock> {
+        // Besides terminators, blocks are only used by `blockaddress`
+        // constants and by terminators that have been removed from their
+        // block, neither of which are edges of the CFG.
+        self.users
+            .by_ref()
+            .filter_map(|user| user.try_as_instruction().and_then(Instruction::parent))
+            .next()
+    }
+}
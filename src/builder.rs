@@ -1,8 +1,244 @@
+use std::marker::PhantomData;
 use std::mem::transmute;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
 
 use super::*;
+use types::Kind;
 
-#[derive(Debug)]
+/// The opcodes accepted by `Builder::build_binop`, a subset of `Opcode`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub enum BinaryOp {
+    Add = 8,
+    FAdd = 9,
+    Sub = 10,
+    FSub = 11,
+    Mul = 12,
+    FMul = 13,
+    UDiv = 14,
+    SDiv = 15,
+    FDiv = 16,
+    URem = 17,
+    SRem = 18,
+    FRem = 19,
+    Shl = 20,
+    LShr = 21,
+    AShr = 22,
+    And = 23,
+    Or = 24,
+    Xor = 25,
+}
+
+impl From<BinaryOp> for LLVMOpcode {
+    fn from(op: BinaryOp) -> LLVMOpcode {
+        unsafe { transmute(op) }
+    }
+}
+
+/// The opcodes accepted by `Builder::build_cast`, a subset of `Opcode`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub enum CastOp {
+    Trunc = 30,
+    ZExt = 31,
+    SExt = 32,
+    FPToUI = 33,
+    FPToSI = 34,
+    UIToFP = 35,
+    SIToFP = 36,
+    FPTrunc = 37,
+    FPExt = 38,
+    PtrToInt = 39,
+    IntToPtr = 40,
+    BitCast = 41,
+    AddrSpaceCast = 60,
+}
+
+impl From<CastOp> for LLVMOpcode {
+    fn from(op: CastOp) -> LLVMOpcode {
+        unsafe { transmute(op) }
+    }
+}
+
+impl CastOp {
+    /// Returns true if a value of type `src` can be cast to `dest` with this
+    /// op, following the same rules as the LLVM verifier. Vectors can be cast
+    /// element-wise, as long as both vectors have the same length.
+    pub fn is_valid(self, src: &Type, dest: &Type) -> bool {
+        let (src_elem, src_len) = split_vector(src);
+        let (dest_elem, dest_len) = split_vector(dest);
+
+        if self != CastOp::BitCast && src_len != dest_len {
+            return false;
+        }
+
+        match (self, src_elem.downcast(), dest_elem.downcast()) {
+            (CastOp::Trunc, Kind::Integer(s), Kind::Integer(d)) => s.width() > d.width(),
+            (CastOp::ZExt, Kind::Integer(s), Kind::Integer(d))
+            | (CastOp::SExt, Kind::Integer(s), Kind::Integer(d)) => s.width() < d.width(),
+
+            (CastOp::FPTrunc, _, _) => match (fp_width(src_elem), fp_width(dest_elem)) {
+                (Some(s), Some(d)) => s > d,
+                _ => false,
+            },
+            (CastOp::FPExt, _, _) => match (fp_width(src_elem), fp_width(dest_elem)) {
+                (Some(s), Some(d)) => s < d,
+                _ => false,
+            },
+
+            (CastOp::FPToUI, _, Kind::Integer(_)) | (CastOp::FPToSI, _, Kind::Integer(_)) => {
+                fp_width(src_elem).is_some()
+            }
+            (CastOp::UIToFP, Kind::Integer(_), _) | (CastOp::SIToFP, Kind::Integer(_), _) => {
+                fp_width(dest_elem).is_some()
+            }
+
+            (CastOp::PtrToInt, Kind::Pointer(_), Kind::Integer(_))
+            | (CastOp::IntToPtr, Kind::Integer(_), Kind::Pointer(_)) => true,
+
+            (CastOp::BitCast, Kind::Pointer(s), Kind::Pointer(d)) => {
+                src_len == dest_len && s.address_space() == d.address_space()
+            }
+            (CastOp::BitCast, Kind::Pointer(_), _) | (CastOp::BitCast, _, Kind::Pointer(_)) => {
+                false
+            }
+            (CastOp::BitCast, _, _) => match (scalar_width(src_elem), scalar_width(dest_elem)) {
+                (Some(s), Some(d)) => s * src_len.unwrap_or(1) == d * dest_len.unwrap_or(1),
+                _ => false,
+            },
+
+            (CastOp::AddrSpaceCast, Kind::Pointer(s), Kind::Pointer(d)) => {
+                s.address_space() != d.address_space()
+            }
+
+            _ => false,
+        }
+    }
+}
+
+// Splits a vector type into its element type and length. Other types are
+// returned as is, without a length.
+fn split_vector(ty: &Type) -> (&Type, Option<u32>) {
+    match ty.downcast() {
+        Kind::Vector(v) => (v.element_type(), Some(v.size())),
+        _ => (ty, None),
+    }
+}
+
+fn fp_width(ty: &Type) -> Option<u32> {
+    match ty.downcast() {
+        Kind::Half(_) => Some(16),
+        Kind::Float(_) => Some(32),
+        Kind::Double(_) => Some(64),
+        Kind::X86_FP80(_) => Some(80),
+        Kind::FP128(_) | Kind::PPC_FP128(_) => Some(128),
+        _ => None,
+    }
+}
+
+// The bit width of a type that can be bitcast, excluding pointers.
+fn scalar_width(ty: &Type) -> Option<u32> {
+    match ty.downcast() {
+        Kind::Integer(t) => Some(t.width()),
+        Kind::X86_MMX(_) => Some(64),
+        _ => fp_width(ty),
+    }
+}
+
+/// The operations performed by `Builder::build_atomic_rmw`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub enum AtomicRMWBinOp {
+    Xchg = 0,
+    Add = 1,
+    Sub = 2,
+    And = 3,
+    Nand = 4,
+    Or = 5,
+    Xor = 6,
+    Max = 7,
+    Min = 8,
+    UMax = 9,
+    UMin = 10,
+}
+
+impl From<AtomicRMWBinOp> for LLVMAtomicRMWBinOp {
+    fn from(op: AtomicRMWBinOp) -> LLVMAtomicRMWBinOp {
+        unsafe { transmute(op) }
+    }
+}
+
+// Returns an error like "build_add: lhs is i32, rhs is i64" unless both
+// operands have the same type.
+fn check_same_type(op: &str, a: (&str, &Value), b: (&str, &Value)) -> Result<()> {
+    let (a_ty, b_ty) = (a.1.get_type(), b.1.get_type());
+
+    if a_ty == b_ty {
+        Ok(())
+    } else {
+        Err(format!("{}: {} is {}, {} is {}", op, a.0, a_ty, b.0, b_ty)
+            .as_str()
+            .into())
+    }
+}
+
+// Returns an error unless `vec_val` is a vector, and `index` is either a
+// variable or a constant that is in bounds. Out of bounds constants are valid
+// IR, but the result is poison, so they are almost certainly a mistake.
+// Constants wider than 64 bits aren't checked.
+fn check_element_index<'a>(
+    op: &str,
+    vec_val: &'a Value,
+    index: &Value,
+) -> Result<&'a types::Vector> {
+    let vec_ty = match vec_val.get_type().downcast() {
+        Kind::Vector(v) => v,
+        _ => {
+            let msg = format!(
+                "{}: vec_val is {}, expected a vector",
+                op,
+                vec_val.get_type()
+            );
+            return Err(msg.as_str().into());
+        }
+    };
+
+    let index_width = match index.get_type().downcast() {
+        Kind::Integer(t) => t.width(),
+        _ => {
+            let msg = format!("{}: index is {}, expected an integer", op, index.get_type());
+            return Err(msg.as_str().into());
+        }
+    };
+
+    if index_width <= 64 && unsafe { !LLVMIsAConstantInt(index.into()).is_null() } {
+        let index = unsafe { LLVMConstIntGetZExtValue(index.into()) };
+        if index >= vec_ty.size() as u64 {
+            let msg = format!("{}: index {} is out of bounds for {}", op, index, vec_ty);
+            return Err(msg.as_str().into());
+        }
+    }
+    Ok(vec_ty)
+}
+
+// Panics unless `agg_val` is a struct or array with a member at `index`.
+fn check_member_index(agg_val: &Value, index: u32) {
+    let len = match agg_val.get_type().downcast() {
+        Kind::Struct(s) => unsafe { LLVMCountStructElementTypes(s.into()) },
+        Kind::Array(a) => a.len(),
+        _ => panic!("{} is not a struct or array", agg_val),
+    };
+
+    assert!(
+        index < len,
+        "member index {} out of bounds for {}",
+        index,
+        agg_val
+    );
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(C)]
 pub enum IntPredicate {
     Eq = 32,
@@ -17,221 +253,1354 @@ pub enum IntPredicate {
     Sle,
 }
 
+/// Predicates for floating point comparisons. `O*` predicates are false if
+/// either operand is a NaN ("ordered"), `U*` predicates are true if either
+/// operand is a NaN ("unordered").
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub enum RealPredicate {
+    False = 0,
+    Oeq,
+    Ogt,
+    Oge,
+    Olt,
+    Ole,
+    One,
+    Ord,
+    Uno,
+    Ueq,
+    Ugt,
+    Uge,
+    Ult,
+    Ule,
+    Une,
+    True,
+}
+
+// Both macros can be given the `Value` subclass that the built instructions
+// are returned as, e.g. `build_ops! { -> Instruction; ... }`, which defaults
+// to `Value`.
 macro_rules! build_named_ops {
-    ($($(#[$attr:meta])*pub fn $name:ident($($argn:ident: $argty:ty),*) { $llvm_fn:path })*) => {
+    (-> $ret:ident; $($(#[$attr:meta])*pub fn $name:ident($($argn:ident: $argty:ty),*) { $llvm_fn:path })*) => {
         $(
             /// Specifying a name is optional; just pass an empty string
-            $(#[$attr])*pub fn $name(&mut self, $($argn: $argty),*, name: &Str) -> LLVMValueRef {
+            $(#[$attr])*pub fn $name(&mut self, $($argn: $argty),*, name: &Str) -> &'ctx $ret {
             unsafe {
-                $llvm_fn(self.as_mut(),
-                         $($argn),*,
-                         name.as_ptr())
+                $ret::from_raw($llvm_fn(self.as_mut(),
+                                        $($argn.into()),*,
+                                        name.as_ptr()))
             }
         })*
+    };
+    ($($t:tt)*) => {
+        build_named_ops! { -> Value; $($t)* }
     }
 }
 macro_rules! build_ops {
-    ($($(#[$attr:meta])*pub fn $name:ident($($argn:ident: $argty:ty),*) { $llvm_fn:path })*) => {
-        $($(#[$attr])*pub fn $name(&mut self, $($argn: $argty),*) -> LLVMValueRef {
-            unsafe { $llvm_fn(self.as_mut(), $($argn),*) }
+    (-> $ret:ident; $($(#[$attr:meta])*pub fn $name:ident($($argn:ident: $argty:ty),*) { $llvm_fn:path })*) => {
+        $($(#[$attr])*pub fn $name(&mut self, $($argn: $argty),*) -> &'ctx $ret {
+            unsafe { $ret::from_raw($llvm_fn(self.as_mut(), $($argn.into()),*)) }
         })*
+    };
+    ($($t:tt)*) => {
+        build_ops! { -> Value; $($t)* }
     }
 }
 
+// Generates checked versions of binary operations built by `build_named_ops!`,
+// which return an error if the operands have different types.
+macro_rules! try_build_binops {
+    ($(pub fn $name:ident => $build:ident)*) => {
+        $(
+            /// Like the method without the `try_` prefix, but returns an
+            /// error if `lhs` and `rhs` have different types.
+            pub fn $name<T: Borrow<Str>>(
+                &mut self,
+                lhs: &'ctx Value,
+                rhs: &'ctx Value,
+                name: &T,
+            ) -> Result<&'ctx Value> {
+                check_same_type(stringify!($build), ("lhs", lhs), ("rhs", rhs))?;
+                Ok(self.$build(lhs, rhs, name.borrow()))
+            }
+        )*
+    };
+}
+
+/// Builds instructions at an insertion point. Every `Value` that a `Builder`
+/// takes or produces belongs to the `Context` that created the `Builder`.
 #[derive(Debug)]
-pub struct Builder {
+pub struct Builder<'ctx> {
     ptr: LLVMBuilderRef,
+    // The instruction that new instructions are inserted before, or null when
+    // they are appended to the block. The C API can't tell, so the positioning
+    // methods keep track of it for `insert_point_guard`.
+    insert_before: LLVMValueRef,
+    _marker: PhantomData<&'ctx Context>,
 }
 
-impl_llvm_ref!(Builder, LLVMBuilderRef);
+impl<'ctx> LLVMRef for Builder<'ctx> {
+    type LLVMRef = LLVMBuilderRef;
+
+    unsafe fn from_raw(ptr: LLVMBuilderRef) -> Self {
+        Builder {
+            ptr,
+            insert_before: ptr::null_mut(),
+            _marker: PhantomData,
+        }
+    }
+
+    unsafe fn as_raw(&self) -> LLVMBuilderRef {
+        self.ptr
+    }
+
+    fn as_mut(&mut self) -> LLVMBuilderRef {
+        self.ptr
+    }
+}
 
 // http://llvm.org/docs/doxygen/html/group__LLVMCCoreInstructionBuilder.html
-//TODO: Get/Set Volatile
-//TODO: Get/Set Ordering
-//TODO: Almost everything from LLVMBuildAdd and upwards
 
-impl Builder {
+impl<'ctx> Builder<'ctx> {
     build_named_ops! {
-        pub fn build_add(lhs: LLVMValueRef, rhs: LLVMValueRef) { LLVMBuildAdd }
-        pub fn build_sub(lhs: LLVMValueRef, rhs: LLVMValueRef) { LLVMBuildSub }
-        pub fn build_mul(lhs: LLVMValueRef, rhs: LLVMValueRef) { LLVMBuildMul }
-
-        pub fn build_fadd(lhs: LLVMValueRef, rhs: LLVMValueRef) { LLVMBuildFAdd }
-        pub fn build_fsub(lhs: LLVMValueRef, rhs: LLVMValueRef) { LLVMBuildFSub }
-        pub fn build_fmul(lhs: LLVMValueRef, rhs: LLVMValueRef) { LLVMBuildFMul }
-        pub fn build_fdiv(lhs: LLVMValueRef, rhs: LLVMValueRef) { LLVMBuildFDiv }
-
-/*pub fn build_nswadd, LLVMBuildNSWAdd, lhs: LLVMValueRef, rhs: LLVMValueRef);
-pub fn build_nswsub, LLVMBuildNSWSub, lhs: LLVMValueRef, rhs: LLVMValueRef);
-pub fn build_nswmul, LLVMBuildNSWMul, lhs: LLVMValueRef, rhs: LLVMValueRef);
-
-pub fn build_nuwadd, LLVMBuildNUWAdd, lhs: LLVMValueRef, rhs: LLVMValueRef);
-pub fn build_nuwsub, LLVMBuildNUWSub, lhs: LLVMValueRef, rhs: LLVMValueRef);
-pub fn build_nuwmul, LLVMBuildNUWMul, lhs: LLVMValueRef, rhs: LLVMValueRef);
-
-pub fn build_udiv, LLVMBuildUDiv, lhs: LLVMValueRef, rhs: LLVMValueRef);
-pub fn build_sdiv, LLVMBuildSDiv, lhs: LLVMValueRef, rhs: LLVMValueRef);
-pub fn build_exact_sdiv, LLVMBuildExactSDiv, lhs: LLVMValueRef, rhs: LLVMValueRef);
-
-pub fn build_urem, LLVMBuildURem, lhs: LLVMValueRef, rhs: LLVMValueRef);
-pub fn build_srem, LLVMBuildSRem, lhs: LLVMValueRef, rhs: LLVMValueRef);
-pub fn build_frem, LLVMBuildFRem, lhs: LLVMValueRef, rhs: LLVMValueRef);
-
-pub fn build_shl, LLVMBuildShl, lhs: LLVMValueRef, rhs: LLVMValueRef);
-pub fn build_lshr, LLVMBuildLShr, lhs: LLVMValueRef, rhs: LLVMValueRef);
-pub fn build_ashr, LLVMBuildAShr, lhs: LLVMValueRef, rhs: LLVMValueRef);
-
-pub fn build_and, LLVMBuildAnd, lhs: LLVMValueRef, rhs: LLVMValueRef);
-pub fn build_or, LLVMBuildOr, lhs: LLVMValueRef, rhs: LLVMValueRef);
-pub fn build_xor, LLVMBuildXor, lhs: LLVMValueRef, rhs: LLVMValueRef);*/
-
-// TODO: LLVMBuildBinOp
-
-        pub fn build_neg(v: LLVMValueRef) { LLVMBuildNeg }
-/*pub fn build_fneg, LLVMBuildFNeg, v: LLVMValueRef);
-pub fn build_nswneg, LLVMBuildNSWNeg, v: LLVMValueRef);
-pub fn build_nuwneg, LLVMBuildNUWNeg, v: LLVMValueRef);
-
-pub fn build_not, LLVMBuildNot, v: LLVMValueRef);
-
-pub fn build_malloc, LLVMBuildMalloc, typ: LLVMTypeRef);
-pub fn build_array_malloc, LLVMBuildArrayMalloc, typ: LLVMTypeRef,
-                                                              val: LLVMValueRef);
-
-pub fn build_alloca, LLVMBuildAlloca, ty: LLVMTypeRef);
-pub fn build_array_alloca, LLVMBuildArrayAlloca, ty: LLVMTypeRef,
-                                                              val: LLVMValueRef);
-
-build_op!(build_free, LLVMBuildFree, pval: LLVMValueRef);
-pub fn build_load, LLVMBuildLoad, ptr: LLVMValueRef);
-build_op!(build_store, LLVMBuildStore,  val: LLVMValueRef, pval: LLVMValueRef);
-
-pub fn build_trunc, LLVMBuildTrunc, val: LLVMValueRef, dest_ty: LLVMTypeRef);
-pub fn build_zext(val: LLVMValueRef, dest_ty: &Type) { LLVMBuildZExt }
-pub fn build_sext, LLVMBuildSExt, val: LLVMValueRef, dest_ty: LLVMTypeRef);
-pub fn build_fp_to_ui, LLVMBuildFPToUI, val: LLVMValueRef, dest_ty: LLVMTypeRef);
-pub fn build_fp_to_si, LLVMBuildFPToSI, val: LLVMValueRef, dest_ty: LLVMTypeRef);
-pub fn build_ui_to_fp, LLVMBuildUIToFP, val: LLVMValueRef, dest_ty: LLVMTypeRef);
-pub fn build_si_to_fp, LLVMBuildSIToFP, val: LLVMValueRef, dest_ty: LLVMTypeRef);
-pub fn build_fp_trunc, LLVMBuildFPTrunc, val: LLVMValueRef, dest_ty: LLVMTypeRef);
-pub fn build_fp_ext, LLVMBuildFPExt, val: LLVMValueRef, dest_ty: LLVMTypeRef);
-pub fn build_ptr_to_int, LLVMBuildPtrToInt, val: LLVMValueRef, dest_ty: LLVMTypeRef);
-pub fn build_int_to_ptr, LLVMBuildIntToPtr, val: LLVMValueRef, dest_ty: LLVMTypeRef);
-
-pub fn build_bit_cast, LLVMBuildBitCast, val: LLVMValueRef, dest_ty: LLVMTypeRef);
-pub fn build_addr_space_cast, LLVMBuildAddrSpaceCast, val: LLVMValueRef,
-                                                                   dest_ty: LLVMTypeRef);
-pub fn build_zext_or_bit_cast, LLVMBuildZExtOrBitCast, val: LLVMValueRef,
-                                                                    dest_ty: LLVMTypeRef);
-pub fn build_sext_or_bit_cast, LLVMBuildSExtOrBitCast, val: LLVMValueRef,
-                                                                    dest_ty: LLVMTypeRef);
-pub fn build_trunc_or_bit_cast, LLVMBuildTruncOrBitCast, val: LLVMValueRef,
-                                                                      dest_ty: LLVMTypeRef);
-
-// TODO: improve LLVMOpcode
-pub fn build_cast, LLVMBuildCast, op: LLVMOpcode,
-                                               val: LLVMValueRef,
-                                               dest_ty: LLVMTypeRef);
-
-pub fn build_pointer_cast, LLVMBuildPointerCast, val: LLVMValueRef, dest_ty: LLVMTypeRef);
-pub fn build_int_cast, LLVMBuildIntCast, val: LLVMValueRef, dest_ty: LLVMTypeRef);
-pub fn build_fpcast, LLVMBuildFPCast, val: LLVMValueRef, dest_ty: LLVMTypeRef);
-
-pub fn build_phi, LLVMBuildPhi, ty: LLVMTypeRef);
-//build_call is manually defined in impl Builder
-pub fn build_select, LLVMBuildSelect, i: LLVMValueRef,
-                                                   the: LLVMValueRef,
-                                                   els: LLVMValueRef);
+        pub fn build_add(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildAdd }
+        pub fn build_sub(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildSub }
+        pub fn build_mul(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildMul }
 
-pub fn build_vaarg, LLVMBuildVAArg, list: LLVMValueRef, ty: LLVMTypeRef);
+        pub fn build_fadd(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildFAdd }
+        pub fn build_fsub(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildFSub }
+        pub fn build_fmul(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildFMul }
+        pub fn build_fdiv(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildFDiv }
+
+        /// Builds an `add nsw`, i.e. an `add` with the "no signed wrap" flag,
+        /// whose result is poison on signed overflow. The flag can't be read or changed
+        /// on the built instruction: the C API of LLVM 7 has no accessors for
+        /// it, as `LLVMGetNSW` and friends were only added in LLVM 17.
+        /// The other `build_nsw*` methods are alike.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// #[macro_use] extern crate llvm;
+        /// # fn main() {
+        /// # let context = llvm::Context::new();
+        /// # let module = context.create_module_with_name(llvmstr!("my module"));
+        /// let i32_type = context.i32_type();
+        /// let func_type = llvm::types::Function::new(i32_type, &[i32_type, i32_type], false);
+        /// let func = module.add_function(func_type, llvmstr!("f"));
+        /// let (a, b) = (func.get_param(0).unwrap(), func.get_param(1).unwrap());
+        /// a.set_name(llvmstr!("a"));
+        /// b.set_name(llvmstr!("b"));
+        ///
+        /// let mut builder = context.create_builder();
+        /// builder.position_at_end(context.append_basic_block(func, llvmstr!("entry")));
+        ///
+        /// let sum = builder.build_nswadd(a, b, llvmstr!("sum"));
+        /// assert_eq!(sum.to_string(), "  %sum = add nsw i32 %a, %b");
+        /// let diff = builder.build_nswsub(a, b, llvmstr!("diff"));
+        /// assert_eq!(diff.to_string(), "  %diff = sub nsw i32 %a, %b");
+        /// let product = builder.build_nswmul(a, b, llvmstr!("product"));
+        /// assert_eq!(product.to_string(), "  %product = mul nsw i32 %a, %b");
+        /// let neg = builder.build_nswneg(a, llvmstr!("neg"));
+        /// assert_eq!(neg.to_string(), "  %neg = sub nsw i32 0, %a");
+        /// # }
+        /// ```
+        pub fn build_nswadd(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildNSWAdd }
+        pub fn build_nswsub(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildNSWSub }
+        pub fn build_nswmul(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildNSWMul }
+
+        /// Builds an `add nuw`, i.e. an `add` with the "no unsigned wrap" flag,
+        /// whose result is poison on unsigned overflow. The flag can't be read or changed
+        /// on the built instruction: the C API of LLVM 7 has no accessors for
+        /// it, as `LLVMGetNSW` and friends were only added in LLVM 17.
+        /// The other `build_nuw*` methods are alike.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// #[macro_use] extern crate llvm;
+        /// # fn main() {
+        /// # let context = llvm::Context::new();
+        /// # let module = context.create_module_with_name(llvmstr!("my module"));
+        /// let i32_type = context.i32_type();
+        /// let func_type = llvm::types::Function::new(i32_type, &[i32_type, i32_type], false);
+        /// let func = module.add_function(func_type, llvmstr!("f"));
+        /// let (a, b) = (func.get_param(0).unwrap(), func.get_param(1).unwrap());
+        /// a.set_name(llvmstr!("a"));
+        /// b.set_name(llvmstr!("b"));
+        ///
+        /// let mut builder = context.create_builder();
+        /// builder.position_at_end(context.append_basic_block(func, llvmstr!("entry")));
+        ///
+        /// let sum = builder.build_nuwadd(a, b, llvmstr!("sum"));
+        /// assert_eq!(sum.to_string(), "  %sum = add nuw i32 %a, %b");
+        /// let diff = builder.build_nuwsub(a, b, llvmstr!("diff"));
+        /// assert_eq!(diff.to_string(), "  %diff = sub nuw i32 %a, %b");
+        /// let product = builder.build_nuwmul(a, b, llvmstr!("product"));
+        /// assert_eq!(product.to_string(), "  %product = mul nuw i32 %a, %b");
+        /// let neg = builder.build_nuwneg(a, llvmstr!("neg"));
+        /// assert_eq!(neg.to_string(), "  %neg = sub nuw i32 0, %a");
+        /// # }
+        /// ```
+        pub fn build_nuwadd(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildNUWAdd }
+        pub fn build_nuwsub(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildNUWSub }
+        pub fn build_nuwmul(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildNUWMul }
+
+        pub fn build_udiv(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildUDiv }
+        pub fn build_sdiv(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildSDiv }
+        /// Builds a `udiv exact`, i.e. a `udiv` with the "exact" flag,
+        /// whose result is poison on a non-zero remainder. The flag can't be read or changed
+        /// on the built instruction: the C API of LLVM 7 has no accessors for
+        /// it, as `LLVMGetExact` and friends were only added in LLVM 17.
+        /// `build_exact_sdiv` is alike.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// #[macro_use] extern crate llvm;
+        /// # fn main() {
+        /// # let context = llvm::Context::new();
+        /// # let module = context.create_module_with_name(llvmstr!("my module"));
+        /// let i32_type = context.i32_type();
+        /// let func_type = llvm::types::Function::new(i32_type, &[i32_type, i32_type], false);
+        /// let func = module.add_function(func_type, llvmstr!("f"));
+        /// let (a, b) = (func.get_param(0).unwrap(), func.get_param(1).unwrap());
+        /// a.set_name(llvmstr!("a"));
+        /// b.set_name(llvmstr!("b"));
+        ///
+        /// let mut builder = context.create_builder();
+        /// builder.position_at_end(context.append_basic_block(func, llvmstr!("entry")));
+        ///
+        /// let quotient = builder.build_exact_udiv(a, b, llvmstr!("quotient"));
+        /// assert_eq!(quotient.to_string(), "  %quotient = udiv exact i32 %a, %b");
+        /// let quotient = builder.build_exact_sdiv(a, b, llvmstr!("quotient"));
+        /// assert_eq!(quotient.to_string(), "  %quotient1 = sdiv exact i32 %a, %b");
+        /// # }
+        /// ```
+        pub fn build_exact_udiv(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildExactUDiv }
+        pub fn build_exact_sdiv(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildExactSDiv }
+
+        pub fn build_urem(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildURem }
+        pub fn build_srem(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildSRem }
+        pub fn build_frem(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildFRem }
+
+        pub fn build_shl(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildShl }
+        pub fn build_lshr(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildLShr }
+        pub fn build_ashr(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildAShr }
+
+        pub fn build_and(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildAnd }
+        pub fn build_or(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildOr }
+        pub fn build_xor(lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildXor }
 
+        /// Builds the binary operator `op`, like the corresponding `build_*`
+        /// method.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// # use llvm::BinaryOp;
+        /// #[macro_use] extern crate llvm;
+        /// # fn main() {
+        /// # let context = llvm::Context::new();
+        /// # let module = context.create_module_with_name(llvmstr!("my module"));
+        /// let i32_type = context.i32_type();
+        /// let func_type = llvm::types::Function::new(i32_type, &[i32_type, i32_type], false);
+        /// let func = module.add_function(func_type, llvmstr!("f"));
+        /// let (a, b) = (func.get_param(0).unwrap(), func.get_param(1).unwrap());
+        /// a.set_name(llvmstr!("a"));
+        /// b.set_name(llvmstr!("b"));
+        ///
+        /// let mut builder = context.create_builder();
+        /// builder.position_at_end(context.append_basic_block(func, llvmstr!("entry")));
+        ///
+        /// let ops = [
+        ///     (BinaryOp::URem, "urem"), (BinaryOp::SRem, "srem"), (BinaryOp::Shl, "shl"),
+        ///     (BinaryOp::LShr, "lshr"), (BinaryOp::AShr, "ashr"), (BinaryOp::And, "and"),
+        ///     (BinaryOp::Or, "or"), (BinaryOp::Xor, "xor"),
+        /// ];
+        /// for &(op, mnemonic) in ops.iter() {
+        ///     let val = builder.build_binop(op, a, b, llvmstr!(""));
+        ///     assert!(val.to_string().contains(&format!("= {} i32 %a, %b", mnemonic)));
+        /// }
+        ///
+        /// assert_eq!(builder.build_shl(a, b, llvmstr!("shl")).to_string(), "  %shl = shl i32 %a, %b");
+        /// assert_eq!(builder.build_xor(a, b, llvmstr!("xor")).to_string(), "  %xor = xor i32 %a, %b");
+        /// assert_eq!(builder.build_not(a, llvmstr!("not")).to_string(), "  %not = xor i32 %a, -1");
+        /// # }
+        /// ```
+        pub fn build_binop(op: BinaryOp, lhs: &'ctx Value, rhs: &'ctx Value) { LLVMBuildBinOp }
 
-pub fn build_extract_element, LLVMBuildExtractElement, vec_val: LLVMValueRef,
-                                                                    index: LLVMValueRef);
-pub fn build_insert_element, LLVMBuildInsertElement, vec_val: LLVMValueRef,
-                                                                  eltval: LLVMValueRef,
-                                                                  index: LLVMValueRef);
-pub fn build_shuffle_vector, LLVMBuildShuffleVector, v1: LLVMValueRef,
-                                                                  v2: LLVMValueRef,
-                                                                  mask: LLVMValueRef);
+        pub fn build_neg(v: &'ctx Value) { LLVMBuildNeg }
+        pub fn build_fneg(v: &'ctx Value) { LLVMBuildFNeg }
+        pub fn build_nswneg(v: &'ctx Value) { LLVMBuildNSWNeg }
+        pub fn build_nuwneg(v: &'ctx Value) { LLVMBuildNUWNeg }
 
-// TODO: Both these types use unsigned, change this to libc::unsigned
-pub fn build_extract_value, LLVMBuildExtractValue, aggval: LLVMValueRef,
-                                                                index: u32);
-pub fn build_insert_value, LLVMBuildInsertValue, aggval: LLVMValueRef,
-                                                              eltval: LLVMValueRef,
-                                                              index: u32);
+        pub fn build_not(v: &'ctx Value) { LLVMBuildNot }
 
-// TODO: LLVMBuildAtomicRMW
+        pub fn build_trunc(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildTrunc }
+        pub fn build_sext(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildSExt }
+        pub fn build_fp_to_ui(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildFPToUI }
+        pub fn build_fp_to_si(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildFPToSI }
+        pub fn build_ui_to_fp(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildUIToFP }
+        pub fn build_si_to_fp(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildSIToFP }
+        pub fn build_fp_trunc(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildFPTrunc }
+        pub fn build_fp_ext(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildFPExt }
+        pub fn build_ptr_to_int(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildPtrToInt }
+        pub fn build_int_to_ptr(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildIntToPtr }
+
+        pub fn build_bit_cast(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildBitCast }
+        pub fn build_addr_space_cast(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildAddrSpaceCast }
+        /// Builds a `zext`, or a `bitcast` if `val` already has the width of
+        /// `dest_ty`.
+        pub fn build_zext_or_bit_cast(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildZExtOrBitCast }
+        /// Builds a `sext`, or a `bitcast` if `val` already has the width of
+        /// `dest_ty`.
+        pub fn build_sext_or_bit_cast(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildSExtOrBitCast }
+        /// Builds a `trunc`, or a `bitcast` if `val` already has the width of
+        /// `dest_ty`.
+        pub fn build_trunc_or_bit_cast(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildTruncOrBitCast }
+
+        /// Builds a cast with any `CastOp`. Invalid casts are only caught by
+        /// the verifier; see `try_build_cast` for a checked version.
+        pub fn build_cast(op: CastOp, val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildCast }
+
+        /// Builds a `bitcast` between pointers in the same address space, or
+        /// a `ptrtoint` if `dest_ty` is an integer.
+        pub fn build_pointer_cast(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildPointerCast }
+        /// Builds a `trunc`, `sext` or `bitcast` depending on the widths of
+        /// `val` and `dest_ty`. Integers are always treated as signed.
+        pub fn build_int_cast(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildIntCast }
+        /// Builds an `fptrunc`, `fpext` or `bitcast` depending on the widths
+        /// of `val` and `dest_ty`.
+        pub fn build_fpcast(val: &'ctx Value, dest_ty: &'ctx Type) { LLVMBuildFPCast }
+
+        /// Builds a `select`, which evaluates to `then` if `cond` is true and
+        /// to `else_` otherwise. If `cond` is a vector of `i1`, the selection
+        /// is done element-wise.
+        pub fn build_select(cond: &'ctx Value,
+                            then: &'ctx Value,
+                            else_: &'ctx Value) { LLVMBuildSelect }
+
+        /// Builds a `shufflevector`, which builds a vector from the elements
+        /// of `v1` and `v2` as selected by `mask`, a constant vector of `i32`
+        /// indices into their concatenation.
+        pub fn build_shuffle_vector(v1: &'ctx Value,
+                                    v2: &'ctx Value,
+                                    mask: &'ctx Value) { LLVMBuildShuffleVector }
+
+/*
+//build_call is manually defined in impl Builder
+pub fn build_vaarg, LLVMBuildVAArg, list: LLVMValueRef, ty: LLVMTypeRef);
 
 pub fn build_is_null, LLVMBuildIsNull, val: LLVMValueRef);
 pub fn build_is_not_null, LLVMBuildIsNotNull, val: LLVMValueRef);
-pub fn build_ptr_diff, LLVMBuildPtrDiff, lhs: LLVMValueRef, rhs: LLVMValueRef);
-pub fn build_fence, LLVMBuildFence, ordering: LLVMAtomicOrdering,
-                                                 singlethread: LLVMBool);*/
+pub fn build_ptr_diff, LLVMBuildPtrDiff, lhs: LLVMValueRef, rhs: LLVMValueRef);*/
+    }
+
+    build_named_ops! { -> Phi;
+        /// Builds an empty `phi` of type `ty`. Its incoming values must be
+        /// added with `Phi::add_incoming` before the function is verified.
+        pub fn build_phi(ty: &'ctx Type) { LLVMBuildPhi }
+    }
+
+    // Memory instructions are returned as `Instruction`s so that their
+    // alignment, volatility and atomic ordering can be set directly.
+    build_named_ops! { -> Instruction;
+        /// Allocates an object of type `ty` in the stack frame of the current
+        /// function, returning a pointer to it.
+        pub fn build_alloca(ty: &'ctx Type) { LLVMBuildAlloca }
+        /// Allocates an array of `size` objects of type `ty` in the stack frame
+        /// of the current function, returning a pointer to the first one.
+        pub fn build_array_alloca(ty: &'ctx Type, size: &'ctx Value) { LLVMBuildArrayAlloca }
+
+        /// Builds a call to `malloc` that allocates an object of type `ty`,
+        /// returning a pointer to it. Note that the result is a `bitcast` of
+        /// the call, unless `ty` is `i8`.
+        pub fn build_malloc(ty: &'ctx Type) { LLVMBuildMalloc }
+        /// Like `build_malloc`, but allocates an array of `size` objects.
+        pub fn build_array_malloc(ty: &'ctx Type, size: &'ctx Value) { LLVMBuildArrayMalloc }
+
+        pub fn build_load(ptr: &'ctx Value) { LLVMBuildLoad }
+    }
+
+    build_ops! { -> Instruction;
+        /// Builds a call to `free` that frees `ptr`.
+        pub fn build_free(ptr: &'ctx Value) { LLVMBuildFree }
+        pub fn build_store(val: &'ctx Value, ptr: &'ctx Value) { LLVMBuildStore }
+
+        /// Atomically applies `op` to the value pointed to by `ptr` and `val`,
+        /// stores the result and returns the original value. If
+        /// `single_thread` is true, the operation is only atomic with respect
+        /// to signal handlers running in the same thread.
+        pub fn build_atomic_rmw(op: AtomicRMWBinOp,
+                                ptr: &'ctx Value,
+                                val: &'ctx Value,
+                                ordering: AtomicOrdering,
+                                single_thread: bool) { LLVMBuildAtomicRMW }
+
+        /// Builds a `cmpxchg` that atomically stores `new` at `ptr` if the
+        /// value there equals `cmp`. It returns a `{ T, i1 }` struct of the
+        /// original value and whether the exchange happened, which can be read
+        /// with `build_extract_value`.
+        ///
+        /// `failure` is the ordering used if the exchange doesn't happen, and
+        /// may not be stronger than `success`, nor be `Release` or
+        /// `AcquireRelease`. `single_thread` works as for `build_atomic_rmw`.
+        /// The exchange is strong; with the `weak-cmpxchg` feature, it can be
+        /// made weak with `Instruction::set_weak`.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// #[macro_use] extern crate llvm;
+        /// use llvm::AtomicOrdering::*;
+        /// # fn main() {
+        /// # let context = llvm::Context::new();
+        /// # let module = context.create_module_with_name(llvmstr!("my module"));
+        /// let i32_type = context.i32_type();
+        /// let func_type = llvm::types::Function::new(
+        ///     context.void_type(),
+        ///     &[i32_type.pointer()],
+        ///     false,
+        /// );
+        /// let func = module.add_function(func_type, llvmstr!("try_lock"));
+        /// # let entry = context.append_basic_block(func, llvmstr!("entry"));
+        /// # let mut builder = context.create_builder();
+        /// # builder.position_at_end(entry);
+        /// let lock = func.get_param(0).unwrap();
+        /// let (unlocked, locked) = (i32_type.constant(0), i32_type.constant(1));
+        /// let xchg = builder.build_atomic_cmpxchg(lock, unlocked, locked, Acquire, Monotonic, false);
+        /// builder.build_ret_void();
+        ///
+        /// assert!(xchg.to_string().contains("cmpxchg i32* %0, i32 0, i32 1 acquire monotonic"));
+        /// assert!(func.verify());
+        /// # }
+        /// ```
+        pub fn build_atomic_cmpxchg(ptr: &'ctx Value,
+                                    cmp: &'ctx Value,
+                                    new: &'ctx Value,
+                                    success: AtomicOrdering,
+                                    failure: AtomicOrdering,
+                                    single_thread: bool) { LLVMBuildAtomicCmpXchg }
+    }
+
+    build_named_ops! { -> Instruction;
+        /// Builds a `fence` that orders memory accesses as specified by
+        /// `ordering`, which must be `Acquire`, `Release`, `AcquireRelease` or
+        /// `SequentiallyConsistent`. If `single_thread` is true, it only
+        /// synchronizes with signal handlers running in the same thread.
+        pub fn build_fence(ordering: AtomicOrdering, single_thread: bool) { LLVMBuildFence }
     }
 
     build_ops! {
-        pub fn build_ret(ret_val: LLVMValueRef) { LLVMBuildRet }
+        pub fn build_ret(ret_val: &'ctx Value) { LLVMBuildRet }
         pub fn build_ret_void() { LLVMBuildRetVoid }
-        pub fn build_br(dest: LLVMBasicBlockRef) { LLVMBuildBr }
+        pub fn build_br(dest: &'ctx BasicBlock) { LLVMBuildBr }
+
+        pub fn build_cond_br(cond: &'ctx Value,
+                             then: &'ctx BasicBlock,
+                             else_: &'ctx BasicBlock) { LLVMBuildCondBr }
+
+        pub fn build_unreachable() { LLVMBuildUnreachable }
+    }
+
+    build_ops! { -> Switch;
+        /// Builds a `switch` on `val` that branches to `else_` until cases are
+        /// added with `Switch::add_case`. `num_cases` is only a hint for how
+        /// much space to reserve.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// #[macro_use] extern crate llvm;
+        /// # fn main() {
+        /// # let context = llvm::Context::new();
+        /// # let module = context.create_module_with_name(llvmstr!("my module"));
+        /// let i32_type = context.i32_type();
+        /// let func_type = llvm::types::Function::new(i32_type, &[i32_type], false);
+        /// let func = module.add_function(func_type, llvmstr!("f"));
+        /// let entry = context.append_basic_block(func, llvmstr!("entry"));
+        /// let one = context.append_basic_block(func, llvmstr!("one"));
+        /// let other = context.append_basic_block(func, llvmstr!("other"));
+        ///
+        /// let mut builder = context.create_builder();
+        /// builder.position_at_end(entry);
+        /// let switch = builder.build_switch(func.get_param(0).unwrap(), other, 1);
+        /// switch.add_case(i32_type.constant(1), one);
+        /// # builder.position_at_end(one);
+        /// # builder.build_ret(i32_type.constant(10));
+        /// # builder.position_at_end(other);
+        /// # builder.build_unreachable();
+        ///
+        /// assert_eq!(entry.successors().count(), 2);
+        /// assert!(func.verify());
+        /// # }
+        /// ```
+        pub fn build_switch(val: &'ctx Value,
+                            else_: &'ctx BasicBlock,
+                            num_cases: u32) { LLVMBuildSwitch }
+    }
+
+    build_ops! { -> IndirectBr;
+        /// Builds an `indirectbr` to `addr`, whose possible destinations must
+        /// be added with `IndirectBr::add_destination`. `num_dests` is only a
+        /// hint for how much space to reserve.
+        pub fn build_indirect_br(addr: &'ctx Value, num_dests: u32) { LLVMBuildIndirectBr }
+    }
+
+    build_ops! {
+        /// Resumes propagating `exn`, an exception that was caught by a
+        /// `landingpad`.
+        pub fn build_resume(exn: &'ctx Value) { LLVMBuildResume }
+        /// Builds a `catchret` that exits `catch_pad` and continues at `dest`.
+        pub fn build_catch_ret(catch_pad: &'ctx Value, dest: &'ctx BasicBlock) { LLVMBuildCatchRet }
+    }
+
+    build_named_ops! { -> LandingPad;
+        /// Builds a `landingpad` of type `ty` that catches exceptions with
+        /// `pers_fn`, which must also be the personality function of the
+        /// current function. Clauses are added with `LandingPad::add_clause`,
+        /// and `num_clauses` is only a hint for how much space to reserve.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// #[macro_use] extern crate llvm;
+        /// # fn main() {
+        /// # let context = llvm::Context::new();
+        /// # let module = context.create_module_with_name(llvmstr!("my module"));
+        /// let void_type = context.void_type();
+        /// let i8_ptr_type = context.i8_type().pointer();
+        /// let personality = module.add_function(
+        ///     llvm::types::Function::new(context.i32_type(), &[], true),
+        ///     llvmstr!("__gxx_personality_v0"),
+        /// );
+        /// let may_throw = module.add_function(
+        ///     llvm::types::Function::new(void_type, &[], false),
+        ///     llvmstr!("may_throw"),
+        /// );
+        ///
+        /// let func = module.add_function(
+        ///     llvm::types::Function::new(void_type, &[], false),
+        ///     llvmstr!("f"),
+        /// );
+        /// func.set_personality_fn(personality);
+        /// let entry = context.append_basic_block(func, llvmstr!("entry"));
+        /// let done = context.append_basic_block(func, llvmstr!("done"));
+        /// let cleanup = context.append_basic_block(func, llvmstr!("cleanup"));
+        ///
+        /// let mut builder = context.create_builder();
+        /// builder.position_at_end(entry);
+        /// builder.build_invoke(may_throw, &[], done, cleanup, llvmstr!(""));
+        /// builder.position_at_end(done);
+        /// builder.build_ret_void();
+        ///
+        /// builder.position_at_end(cleanup);
+        /// let exn = builder.build_landing_pad(i8_ptr_type, personality, 0, llvmstr!("exn"));
+        /// exn.set_cleanup(true);
+        /// builder.build_resume(exn);
+        ///
+        /// assert!(func.verify());
+        /// # }
+        /// ```
+        pub fn build_landing_pad(ty: &'ctx Type,
+                                 pers_fn: &'ctx Value,
+                                 num_clauses: u32) { LLVMBuildLandingPad }
+    }
+
+    /// Builds an `invoke` of `func`, which continues at `then` if the call
+    /// returns normally, or at `catch` if it throws an exception. `catch`
+    /// must begin with a `landingpad`, or with a funclet pad instruction.
+    pub fn build_invoke<T: Borrow<Str>>(
+        &mut self,
+        func: &'ctx Value,
+        args: &[&'ctx Value],
+        then: &'ctx BasicBlock,
+        catch: &'ctx BasicBlock,
+        name: &T,
+    ) -> &'ctx CallSite {
+        unsafe {
+            CallSite::from_raw(LLVMBuildInvoke(
+                self.as_mut(),
+                func.into(),
+                transmute::<*const &Value, *mut LLVMValueRef>(args.as_ptr()),
+                args.len() as u32,
+                then.into(),
+                catch.into(),
+                name.borrow().as_ptr(),
+            ))
+        }
+    }
+
+    /// Builds a `catchswitch` within `parent_pad`, or at the top level if it
+    /// is `None`. Exceptions that aren't handled unwind to `unwind`, or to the
+    /// caller if it is `None`. Handlers are added with
+    /// `CatchSwitch::add_handler`, and `num_handlers` is only a hint for how
+    /// much space to reserve.
+    pub fn build_catch_switch<T: Borrow<Str>>(
+        &mut self,
+        parent_pad: Option<&'ctx Value>,
+        unwind: Option<&'ctx BasicBlock>,
+        num_handlers: u32,
+        name: &T,
+    ) -> &'ctx CatchSwitch {
+        unsafe {
+            CatchSwitch::from_raw(LLVMBuildCatchSwitch(
+                self.as_mut(),
+                parent_pad.map_or(ptr::null_mut(), LLVMValueRef::from),
+                unwind.map_or(ptr::null_mut(), LLVMBasicBlockRef::from),
+                num_handlers,
+                name.borrow().as_ptr(),
+            ))
+        }
+    }
+
+    /// Builds a `catchpad`, which must be the first instruction of a handler
+    /// of `catch_switch`. The meaning of `args` depends on the personality
+    /// function.
+    pub fn build_catch_pad<T: Borrow<Str>>(
+        &mut self,
+        catch_switch: &'ctx CatchSwitch,
+        args: &[&'ctx Value],
+        name: &T,
+    ) -> &'ctx Value {
+        unsafe {
+            Value::from_raw(LLVMBuildCatchPad(
+                self.as_mut(),
+                catch_switch.into(),
+                transmute::<*const &Value, *mut LLVMValueRef>(args.as_ptr()),
+                args.len() as u32,
+                name.borrow().as_ptr(),
+            ))
+        }
+    }
+
+    /// Builds a `cleanuppad` within `parent_pad`, or at the top level if it
+    /// is `None`. The meaning of `args` depends on the personality function.
+    pub fn build_cleanup_pad<T: Borrow<Str>>(
+        &mut self,
+        parent_pad: Option<&'ctx Value>,
+        args: &[&'ctx Value],
+        name: &T,
+    ) -> &'ctx Value {
+        unsafe {
+            Value::from_raw(LLVMBuildCleanupPad(
+                self.as_mut(),
+                parent_pad.map_or(ptr::null_mut(), LLVMValueRef::from),
+                transmute::<*const &Value, *mut LLVMValueRef>(args.as_ptr()),
+                args.len() as u32,
+                name.borrow().as_ptr(),
+            ))
+        }
+    }
+
+    /// Builds a `cleanupret` that exits `cleanup_pad` and unwinds to
+    /// `unwind`, or to the caller if it is `None`.
+    pub fn build_cleanup_ret(
+        &mut self,
+        cleanup_pad: &'ctx Value,
+        unwind: Option<&'ctx BasicBlock>,
+    ) -> &'ctx Value {
+        unsafe {
+            Value::from_raw(LLVMBuildCleanupRet(
+                self.as_mut(),
+                cleanup_pad.into(),
+                unwind.map_or(ptr::null_mut(), LLVMBasicBlockRef::from),
+            ))
+        }
+    }
+
+    /// Extracts the element at `index` from the vector `vec_val`. The result
+    /// is poison if `index` is out of bounds; see `try_build_extract_element`
+    /// for a checked version.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `vec_val` isn't a vector.
+    pub fn build_extract_element<T: Borrow<Str>>(
+        &mut self,
+        vec_val: &'ctx Value,
+        index: &'ctx Value,
+        name: &T,
+    ) -> &'ctx Value {
+        assert!(
+            vec_val.get_type().try_as_vector().is_some(),
+            "{} is not a vector",
+            vec_val
+        );
+        unsafe {
+            Value::from_raw(LLVMBuildExtractElement(
+                self.as_mut(),
+                vec_val.into(),
+                index.into(),
+                name.borrow().as_ptr(),
+            ))
+        }
+    }
 
-        pub fn build_cond_br(cond: LLVMValueRef,
-                             then: LLVMBasicBlockRef,
-                             else_: LLVMBasicBlockRef) { LLVMBuildCondBr }
+    /// Returns a copy of the vector `vec_val` with the element at `index`
+    /// replaced by `elt_val`. The result is poison if `index` is out of
+    /// bounds; see `try_build_insert_element` for a checked version.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `vec_val` isn't a vector.
+    pub fn build_insert_element<T: Borrow<Str>>(
+        &mut self,
+        vec_val: &'ctx Value,
+        elt_val: &'ctx Value,
+        index: &'ctx Value,
+        name: &T,
+    ) -> &'ctx Value {
+        assert!(
+            vec_val.get_type().try_as_vector().is_some(),
+            "{} is not a vector",
+            vec_val
+        );
+        unsafe {
+            Value::from_raw(LLVMBuildInsertElement(
+                self.as_mut(),
+                vec_val.into(),
+                elt_val.into(),
+                index.into(),
+                name.borrow().as_ptr(),
+            ))
+        }
+    }
+
+    /// Extracts the member at `index` from `agg_val`, a struct or array.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// # let i32_type = context.i32_type();
+    /// # let func_type = llvm::types::Function::new(i32_type, &[i32_type.pointer()], false);
+    /// # let func = module.add_function(func_type, llvmstr!("f"));
+    /// # let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// # let mut builder = context.create_builder();
+    /// # builder.position_at_end(entry);
+    /// # let ptr = func.get_param(0).unwrap();
+    /// let (zero, one) = (i32_type.constant(0), i32_type.constant(1));
+    /// let ordering = llvm::AtomicOrdering::SequentiallyConsistent;
+    ///
+    /// // `cmpxchg` returns a `{ i32, i1 }` pair
+    /// let pair = builder.build_atomic_cmpxchg(ptr, zero, one, ordering, ordering, false);
+    /// let old = builder.build_extract_value(pair, 0, llvmstr!("old"));
+    /// assert_eq!(old.get_type().to_string(), "i32");
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `agg_val` isn't a struct or array, or if `index` is out of
+    /// bounds.
+    pub fn build_extract_value<T: Borrow<Str>>(
+        &mut self,
+        agg_val: &'ctx Value,
+        index: u32,
+        name: &T,
+    ) -> &'ctx Value {
+        check_member_index(agg_val, index);
+        unsafe {
+            Value::from_raw(LLVMBuildExtractValue(
+                self.as_mut(),
+                agg_val.into(),
+                index,
+                name.borrow().as_ptr(),
+            ))
+        }
+    }
+
+    /// Returns a copy of `agg_val`, a struct or array, with the member at
+    /// `index` replaced by `elt_val`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `agg_val` isn't a struct or array, or if `index` is out of
+    /// bounds.
+    pub fn build_insert_value<T: Borrow<Str>>(
+        &mut self,
+        agg_val: &'ctx Value,
+        elt_val: &'ctx Value,
+        index: u32,
+        name: &T,
+    ) -> &'ctx Value {
+        check_member_index(agg_val, index);
+        unsafe {
+            Value::from_raw(LLVMBuildInsertValue(
+                self.as_mut(),
+                agg_val.into(),
+                elt_val.into(),
+                index,
+                name.borrow().as_ptr(),
+            ))
+        }
+    }
+
+    /// Builds a `ret` of multiple values, which are returned as a struct
+    /// whose element types match the types of `ret_vals`.
+    pub fn build_aggregate_ret(&mut self, ret_vals: &[&'ctx Value]) -> &'ctx Value {
+        unsafe {
+            Value::from_raw(LLVMBuildAggregateRet(
+                self.as_mut(),
+                transmute::<*const &Value, *mut LLVMValueRef>(ret_vals.as_ptr()),
+                ret_vals.len() as u32,
+            ))
+        }
     }
 
     pub fn build_zext<T: Borrow<Str>>(
         &mut self,
-        val: LLVMValueRef,
-        dst_ty: &Type,
+        val: &'ctx Value,
+        dst_ty: &'ctx Type,
+        name: &T,
+    ) -> &'ctx Value {
+        unsafe {
+            Value::from_raw(LLVMBuildZExt(
+                self.as_mut(),
+                val.into(),
+                dst_ty.into(),
+                name.borrow().as_ptr(),
+            ))
+        }
+    }
+
+    /// Like `build_cast`, but returns an error instead of building the cast
+    /// if `op` can't cast `val` to `dest_ty`, as checked by `CastOp::is_valid`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// # let i32_type = context.i32_type();
+    /// # let func_type = llvm::types::Function::new(i32_type, &[i32_type], false);
+    /// # let func = module.add_function(func_type, llvmstr!("f"));
+    /// # let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// # let mut builder = context.create_builder();
+    /// # builder.position_at_end(entry);
+    /// let x = func.get_param(0).unwrap();
+    /// let err = builder
+    ///     .try_build_cast(llvm::CastOp::Trunc, x, context.i64_type(), llvmstr!(""))
+    ///     .unwrap_err();
+    /// assert_eq!(err.to_string(), "build_cast: cannot trunc i32 to i64");
+    /// # }
+    /// ```
+    pub fn try_build_cast<T: Borrow<Str>>(
+        &mut self,
+        op: CastOp,
+        val: &'ctx Value,
+        dest_ty: &'ctx Type,
+        name: &T,
+    ) -> Result<&'ctx Value> {
+        let src_ty = val.get_type();
+
+        if op.is_valid(src_ty, dest_ty) {
+            Ok(self.build_cast(op, val, dest_ty, name.borrow()))
+        } else {
+            let op = format!("{:?}", op).to_lowercase();
+            Err(format!("build_cast: cannot {} {} to {}", op, src_ty, dest_ty)
+                .as_str()
+                .into())
+        }
+    }
+
+    try_build_binops! {
+        pub fn try_build_add => build_add
+        pub fn try_build_sub => build_sub
+        pub fn try_build_mul => build_mul
+
+        pub fn try_build_fadd => build_fadd
+        pub fn try_build_fsub => build_fsub
+        pub fn try_build_fmul => build_fmul
+        pub fn try_build_fdiv => build_fdiv
+
+        pub fn try_build_nswadd => build_nswadd
+        pub fn try_build_nswsub => build_nswsub
+        pub fn try_build_nswmul => build_nswmul
+
+        pub fn try_build_nuwadd => build_nuwadd
+        pub fn try_build_nuwsub => build_nuwsub
+        pub fn try_build_nuwmul => build_nuwmul
+
+        pub fn try_build_udiv => build_udiv
+        pub fn try_build_sdiv => build_sdiv
+        pub fn try_build_exact_udiv => build_exact_udiv
+        pub fn try_build_exact_sdiv => build_exact_sdiv
+
+        pub fn try_build_urem => build_urem
+        pub fn try_build_srem => build_srem
+        pub fn try_build_frem => build_frem
+
+        pub fn try_build_shl => build_shl
+        pub fn try_build_lshr => build_lshr
+        pub fn try_build_ashr => build_ashr
+
+        pub fn try_build_and => build_and
+        pub fn try_build_or => build_or
+        pub fn try_build_xor => build_xor
+    }
+
+    /// Like `build_binop`, but returns an error if `lhs` and `rhs` have
+    /// different types. The other `try_build_*` methods check their operands
+    /// the same way before building anything.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let (i32_type, i64_type) = (context.i32_type(), context.i64_type());
+    /// let func_type = llvm::types::Function::new(i32_type, &[i32_type, i64_type], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// # let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// # let mut builder = context.create_builder();
+    /// # builder.position_at_end(entry);
+    /// let (x, y) = (func.get_param(0).unwrap(), func.get_param(1).unwrap());
+    ///
+    /// let err = builder.try_build_add(x, y, llvmstr!("sum")).unwrap_err();
+    /// assert_eq!(err.to_string(), "build_add: lhs is i32, rhs is i64");
+    ///
+    /// let y = builder.build_trunc(y, i32_type, llvmstr!("y"));
+    /// assert!(builder.try_build_binop(llvm::BinaryOp::Add, x, y, llvmstr!("sum")).is_ok());
+    /// # }
+    /// ```
+    pub fn try_build_binop<T: Borrow<Str>>(
+        &mut self,
+        op: BinaryOp,
+        lhs: &'ctx Value,
+        rhs: &'ctx Value,
+        name: &T,
+    ) -> Result<&'ctx Value> {
+        check_same_type("build_binop", ("lhs", lhs), ("rhs", rhs))?;
+        Ok(self.build_binop(op, lhs, rhs, name.borrow()))
+    }
+
+    /// Like `build_icmp`, but returns an error if `lhs` and `rhs` have
+    /// different types.
+    pub fn try_build_icmp<T: Borrow<Str>>(
+        &mut self,
+        op: IntPredicate,
+        lhs: &'ctx Value,
+        rhs: &'ctx Value,
+        name: &T,
+    ) -> Result<&'ctx Value> {
+        check_same_type("build_icmp", ("lhs", lhs), ("rhs", rhs))?;
+        Ok(self.build_icmp(op, lhs, rhs, name))
+    }
+
+    /// Like `build_fcmp`, but returns an error if `lhs` and `rhs` have
+    /// different types.
+    pub fn try_build_fcmp<T: Borrow<Str>>(
+        &mut self,
+        op: RealPredicate,
+        lhs: &'ctx Value,
+        rhs: &'ctx Value,
+        name: &T,
+    ) -> Result<&'ctx Value> {
+        check_same_type("build_fcmp", ("lhs", lhs), ("rhs", rhs))?;
+        Ok(self.build_fcmp(op, lhs, rhs, name))
+    }
+
+    /// Like `build_store`, but returns an error unless `ptr` is a pointer to
+    /// the type of `val`.
+    pub fn try_build_store(
+        &mut self,
+        val: &'ctx Value,
+        ptr: &'ctx Value,
+    ) -> Result<&'ctx Instruction> {
+        let pointee = match ptr.get_type().downcast() {
+            Kind::Pointer(p) => Some(p.pointee_type()),
+            _ => None,
+        };
+
+        if pointee == Some(val.get_type()) {
+            Ok(self.build_store(val, ptr))
+        } else {
+            let msg = format!(
+                "build_store: val is {}, ptr is {}",
+                val.get_type(),
+                ptr.get_type()
+            );
+            Err(msg.as_str().into())
+        }
+    }
+
+    /// Like `build_select`, but returns an error if `then` and `else_` have
+    /// different types, or if `cond` isn't an `i1` or a vector of them.
+    pub fn try_build_select<T: Borrow<Str>>(
+        &mut self,
+        cond: &'ctx Value,
+        then: &'ctx Value,
+        else_: &'ctx Value,
+        name: &T,
+    ) -> Result<&'ctx Value> {
+        let (cond_elem, _) = split_vector(cond.get_type());
+        match cond_elem.downcast() {
+            Kind::Integer(t) if t.width() == 1 => {}
+            _ => {
+                let msg = format!("build_select: cond is {}, expected i1", cond.get_type());
+                return Err(msg.as_str().into());
+            }
+        }
+
+        check_same_type("build_select", ("then", then), ("else", else_))?;
+        Ok(self.build_select(cond, then, else_, name.borrow()))
+    }
+
+    /// Like `build_extract_element`, but returns an error instead of
+    /// panicking if `vec_val` isn't a vector, and also if `index` is a
+    /// constant that is out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i32_type = context.i32_type();
+    /// let vec_type = i32_type.vector(4).unwrap();
+    /// # let func_type = llvm::types::Function::new(i32_type, &[vec_type], false);
+    /// # let func = module.add_function(func_type, llvmstr!("f"));
+    /// # let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// # let mut builder = context.create_builder();
+    /// # builder.position_at_end(entry);
+    /// let v = func.get_param(0).unwrap();
+    ///
+    /// assert!(builder.try_build_extract_element(v, i32_type.constant(3), llvmstr!("")).is_ok());
+    /// let err = builder.try_build_extract_element(v, i32_type.constant(7), llvmstr!(""));
+    /// assert_eq!(
+    ///     err.unwrap_err().to_string(),
+    ///     "build_extract_element: index 7 is out of bounds for <4 x i32>"
+    /// );
+    /// # }
+    /// ```
+    pub fn try_build_extract_element<T: Borrow<Str>>(
+        &mut self,
+        vec_val: &'ctx Value,
+        index: &'ctx Value,
         name: &T,
-    ) -> LLVMValueRef {
-        unsafe { LLVMBuildZExt(self.as_mut(), val, dst_ty.into(), name.borrow().as_ptr()) }
+    ) -> Result<&'ctx Value> {
+        check_element_index("build_extract_element", vec_val, index)?;
+        Ok(self.build_extract_element(vec_val, index, name))
+    }
+
+    /// Like `build_insert_element`, but returns an error instead of panicking
+    /// if `vec_val` isn't a vector, and also if `elt_val` doesn't have the
+    /// element type of `vec_val` or `index` is a constant that is out of
+    /// bounds.
+    pub fn try_build_insert_element<T: Borrow<Str>>(
+        &mut self,
+        vec_val: &'ctx Value,
+        elt_val: &'ctx Value,
+        index: &'ctx Value,
+        name: &T,
+    ) -> Result<&'ctx Value> {
+        let vec_ty = check_element_index("build_insert_element", vec_val, index)?;
+        if elt_val.get_type() != vec_ty.element_type() {
+            let msg = format!(
+                "build_insert_element: elt_val is {}, vec_val is {}",
+                elt_val.get_type(),
+                vec_ty
+            );
+            return Err(msg.as_str().into());
+        }
+        Ok(self.build_insert_element(vec_val, elt_val, index, name))
+    }
+
+    /// Like `build_call`, but returns an error unless `func` is a function
+    /// (pointer) whose parameter types match the types of `args`.
+    pub fn try_build_call<T: Borrow<Str>>(
+        &mut self,
+        func: &'ctx Value,
+        args: &[&'ctx Value],
+        name: &T,
+    ) -> Result<&'ctx CallSite> {
+        let func_ty = match func.get_type().downcast() {
+            Kind::Pointer(p) => p.pointee_type(),
+            _ => func.get_type(),
+        };
+        let func_ty = match func_ty.downcast() {
+            Kind::Function(f) => f,
+            _ => {
+                let msg = format!(
+                    "build_call: callee is {}, expected a function",
+                    func.get_type()
+                );
+                return Err(msg.as_str().into());
+            }
+        };
+
+        let param_count = func_ty.param_count();
+        let arg_count = args.len() as u32;
+        if arg_count < param_count || (arg_count > param_count && !func_ty.is_var_arg()) {
+            let msg = format!(
+                "build_call: expected {} arguments, got {}",
+                param_count, arg_count
+            );
+            return Err(msg.as_str().into());
+        }
+
+        for (i, (arg, param_ty)) in args.iter().zip(func_ty.param_types()).enumerate() {
+            if arg.get_type() != param_ty {
+                let msg = format!(
+                    "build_call: argument {} is {}, expected {}",
+                    i,
+                    arg.get_type(),
+                    param_ty
+                );
+                return Err(msg.as_str().into());
+            }
+        }
+
+        Ok(self.build_call(func, args, name))
     }
 
     pub fn build_icmp<T: Borrow<Str>>(
         &mut self,
         op: IntPredicate,
-        lhs: LLVMValueRef,
-        rhs: LLVMValueRef,
+        lhs: &'ctx Value,
+        rhs: &'ctx Value,
         name: &T,
-    ) -> LLVMValueRef {
+    ) -> &'ctx Value {
         unsafe {
-            LLVMBuildICmp(
+            Value::from_raw(LLVMBuildICmp(
                 self.as_mut(),
                 transmute(op),
-                lhs,
-                rhs,
+                lhs.into(),
+                rhs.into(),
                 name.borrow().as_ptr(),
-            )
+            ))
         }
     }
 
-    // TODO: pub fn build_fcmp
+    /// Builds an `fcmp` instruction, whose predicate can be read back with
+    /// `Instruction::fcmp_predicate`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let double_type = context.double_type();
+    /// let func_type = llvm::types::Function::new(context.i1_type(), &[double_type, double_type], false);
+    /// let func = module.add_function(func_type, llvmstr!("lt"));
+    /// # let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// # let mut builder = context.create_builder();
+    /// # builder.position_at_end(entry);
+    /// let (x, y) = (func.get_param(0).unwrap(), func.get_param(1).unwrap());
+    /// let cmp = builder.build_fcmp(llvm::RealPredicate::Olt, x, y, llvmstr!("cmp"));
+    ///
+    /// let inst = cmp.try_as_instruction().unwrap();
+    /// assert_eq!(inst.fcmp_predicate(), Some(llvm::RealPredicate::Olt));
+    /// # }
+    /// ```
+    pub fn build_fcmp<T: Borrow<Str>>(
+        &mut self,
+        op: RealPredicate,
+        lhs: &'ctx Value,
+        rhs: &'ctx Value,
+        name: &T,
+    ) -> &'ctx Value {
+        unsafe {
+            Value::from_raw(LLVMBuildFCmp(
+                self.as_mut(),
+                transmute(op),
+                lhs.into(),
+                rhs.into(),
+                name.borrow().as_ptr(),
+            ))
+        }
+    }
 
     // TODO: check which methods should borrow mutably
 
-    pub fn position_at_end(&mut self, basic_block: LLVMBasicBlockRef) {
+    /// Positions the builder at the end of `basic_block`. The block has to
+    /// outlive the builder, so a builder can't be used after the function it
+    /// builds into is gone:
+    ///
+    /// ```rust,compile_fail
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// let mut builder = context.create_builder();
+    /// {
+    ///     let module = context.create_module_with_name(llvmstr!("my module"));
+    ///     let func_type = llvm::types::Function::new(context.void_type(), &[], false);
+    ///     let func = module.add_function(func_type, llvmstr!("f"));
+    ///     builder.position_at_end(context.append_basic_block(func, llvmstr!("entry")));
+    /// }
+    /// builder.build_ret_void();
+    /// # }
+    /// ```
+    pub fn position_at_end(&mut self, basic_block: &'ctx BasicBlock) {
         unsafe {
-            LLVMPositionBuilderAtEnd(self.as_mut(), basic_block);
+            LLVMPositionBuilderAtEnd(self.as_mut(), basic_block.into());
+        }
+        self.insert_before = ptr::null_mut();
+    }
+
+    /// Positions the builder so that instructions are inserted immediately
+    /// before `inst`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `inst` doesn't belong to a block.
+    pub fn position_before(&mut self, inst: &'ctx Instruction) {
+        assert!(
+            inst.parent().is_some(),
+            "{} doesn't belong to a block",
+            inst
+        );
+        unsafe { LLVMPositionBuilderBefore(self.as_mut(), inst.into()) }
+        self.insert_before = inst.into();
+    }
+
+    /// Positions the builder before `inst`, which must belong to `block`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `inst` doesn't belong to `block`.
+    pub fn position(&mut self, block: &'ctx BasicBlock, inst: &'ctx Instruction) {
+        assert!(
+            inst.parent() == Some(block),
+            "{} doesn't belong to the given block",
+            inst
+        );
+        unsafe { LLVMPositionBuilder(self.as_mut(), block.into(), inst.into()) }
+        self.insert_before = inst.into();
+    }
+
+    /// Returns the block that instructions are inserted into, or `None` if
+    /// the builder isn't positioned.
+    pub fn insertion_block(&self) -> Option<&'ctx BasicBlock> {
+        let block = unsafe { LLVMGetInsertBlock(self.as_raw()) };
+
+        if block.is_null() {
+            None
+        } else {
+            unsafe { Some(BasicBlock::from_raw_block(block)) }
+        }
+    }
+
+    /// Unpositions the builder, so that it must be positioned again before
+    /// building any instructions.
+    pub fn clear_insertion_position(&mut self) {
+        unsafe { LLVMClearInsertionPosition(self.as_mut()) }
+        self.insert_before = ptr::null_mut();
+    }
+
+    /// Returns a guard that restores the current position of the builder when
+    /// dropped. The builder can be used through the guard in the meantime.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i32_type = context.i32_type();
+    /// let func_type = llvm::types::Function::new(i32_type, &[], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// let body = context.append_basic_block(func, llvmstr!("body"));
+    ///
+    /// let mut builder = context.create_builder();
+    /// builder.position_at_end(entry);
+    /// builder.build_br(body);
+    /// builder.position_at_end(body);
+    ///
+    /// // Emit an alloca at the start of the entry block...
+    /// let slot = {
+    ///     let mut guard = builder.insert_point_guard();
+    ///     guard.position_before(entry.first_instruction().unwrap());
+    ///     guard.build_alloca(i32_type, llvmstr!("slot"))
+    /// };
+    ///
+    /// // ...and continue in the body.
+    /// assert_eq!(builder.insertion_block(), Some(body));
+    /// let val = builder.build_load(slot, llvmstr!("val"));
+    /// builder.build_ret(val);
+    /// assert!(func.verify());
+    /// # }
+    /// ```
+    pub fn insert_point_guard<'a>(&'a mut self) -> InsertPointGuard<'a, 'ctx> {
+        let block = self.insertion_block();
+        let before = if self.insert_before.is_null() {
+            None
+        } else {
+            unsafe { Some(Instruction::from_raw(self.insert_before)) }
+        };
+
+        InsertPointGuard {
+            builder: self,
+            block,
+            before,
         }
     }
 
+    /// Inserts `inst`, which must not belong to any block yet, e.g. the
+    /// result of `Instruction::clone_instruction`, at the current position.
+    pub fn insert(&mut self, inst: &'ctx Instruction) {
+        unsafe { LLVMInsertIntoBuilder(self.as_mut(), inst.into()) }
+    }
+
+    /// Like `insert`, but also names the instruction.
+    pub fn insert_with_name<T: Borrow<Str>>(&mut self, inst: &'ctx Instruction, name: &T) {
+        unsafe { LLVMInsertIntoBuilderWithName(self.as_mut(), inst.into(), name.borrow().as_ptr()) }
+    }
+
+    /// Sets the debug location, a `DILocation` wrapped as a value, that is
+    /// attached to all instructions built from now on. Pass `None` to stop
+    /// attaching debug locations.
+    ///
+    /// # Safety
+    ///
+    /// `loc` must wrap a `DILocation`. LLVM casts it without checking, and the
+    /// C API of LLVM 7 can't tell a `DILocation` from other metadata.
+    // TODO: a getter needs LLVMGetCurrentDebugLocation2 (LLVM 9), as the old
+    // version wraps a missing location as a non-null value.
+    pub unsafe fn set_current_debug_location(&mut self, loc: Option<&'ctx MetadataAsValue>) {
+        LLVMSetCurrentDebugLocation(
+            self.as_mut(),
+            loc.map_or(ptr::null_mut(), LLVMValueRef::from),
+        )
+    }
+
+    /// Attaches the current debug location to `inst`, e.g. after inserting it
+    /// with `insert`.
+    pub fn set_inst_debug_location(&mut self, inst: &Instruction) {
+        unsafe { LLVMSetInstDebugLocation(self.as_mut(), inst.into()) }
+    }
+
+    /// Builds a `call` of `func`, which can be configured through the
+    /// returned `CallSite`, e.g. to set the calling convention.
     pub fn build_call<T: Borrow<Str>>(
         &mut self,
-        func: LLVMValueRef,
-        args: &[LLVMValueRef],
+        func: &'ctx Value,
+        args: &[&'ctx Value],
         name: &T,
-    ) -> LLVMValueRef {
+    ) -> &'ctx CallSite {
         unsafe {
-            LLVMBuildCall(
+            CallSite::from_raw(LLVMBuildCall(
                 self.as_mut(),
-                func,
-                args.as_ptr() as *mut LLVMValueRef,
+                func.into(),
+                transmute::<*const &Value, *mut LLVMValueRef>(args.as_ptr()),
                 args.len() as u32,
                 name.borrow().as_ptr(),
-            )
+            ))
         }
     }
 
@@ -239,8 +1608,14 @@ pub fn build_fence, LLVMBuildFence, ordering: LLVMAtomicOrdering,
         &self,
         s: &S,
         name: &T,
-    ) -> LLVMValueRef {
-        unsafe { LLVMBuildGlobalString(self.as_raw(), s.borrow().as_ptr(), name.borrow().as_ptr()) }
+    ) -> &'ctx Value {
+        unsafe {
+            Value::from_raw(LLVMBuildGlobalString(
+                self.as_raw(),
+                s.borrow().as_ptr(),
+                name.borrow().as_ptr(),
+            ))
+        }
     }
 
     /// NOTE: requires a function and basic block to be present
@@ -248,48 +1623,92 @@ pub fn build_fence, LLVMBuildFence, ordering: LLVMAtomicOrdering,
         &self,
         s: &S,
         name: &T,
-    ) -> LLVMValueRef {
+    ) -> &'ctx Value {
         unsafe {
-            LLVMBuildGlobalStringPtr(self.as_raw(), s.borrow().as_ptr(), name.borrow().as_ptr())
+            Value::from_raw(LLVMBuildGlobalStringPtr(
+                self.as_raw(),
+                s.borrow().as_ptr(),
+                name.borrow().as_ptr(),
+            ))
         }
     }
 
     pub fn build_in_bounds_gep<T: Borrow<Str>>(
         &self,
-        ptr: LLVMValueRef,
-        mut indices: Vec<LLVMValueRef>,
+        ptr: &'ctx Value,
+        indices: &[&'ctx Value],
         name: &T,
-    ) -> LLVMValueRef {
+    ) -> &'ctx Value {
         unsafe {
-            LLVMBuildInBoundsGEP(
+            Value::from_raw(LLVMBuildInBoundsGEP(
                 self.as_raw(),
-                ptr,
-                indices.as_mut_ptr(),
+                ptr.into(),
+                transmute::<*const &Value, *mut LLVMValueRef>(indices.as_ptr()),
                 indices.len() as u32,
                 name.borrow().as_ptr(),
-            )
+            ))
         }
     }
     pub fn build_gep<T: Borrow<Str>>(
         &self,
-        ptr: LLVMValueRef,
-        mut indices: Vec<LLVMValueRef>,
+        ptr: &'ctx Value,
+        indices: &[&'ctx Value],
         name: &T,
-    ) -> LLVMValueRef {
+    ) -> &'ctx Value {
         unsafe {
-            LLVMBuildGEP(
+            Value::from_raw(LLVMBuildGEP(
                 self.as_raw(),
-                ptr,
-                indices.as_mut_ptr(),
+                ptr.into(),
+                transmute::<*const &Value, *mut LLVMValueRef>(indices.as_ptr()),
                 indices.len() as u32,
                 name.borrow().as_ptr(),
-            )
+            ))
         }
     }
 }
 
-impl Drop for Builder {
+impl<'ctx> Drop for Builder<'ctx> {
     fn drop(&mut self) {
         unsafe { LLVMDisposeBuilder(self.as_mut()) };
     }
 }
+
+/// Restores the position that a `Builder` had when
+/// `Builder::insert_point_guard` was called, once dropped. Derefs to the
+/// `Builder` in the meantime.
+///
+/// If the builder was positioned before an instruction that has since been
+/// removed from its block, the builder is positioned at the end of the block
+/// instead.
+#[derive(Debug)]
+pub struct InsertPointGuard<'a, 'ctx: 'a> {
+    builder: &'a mut Builder<'ctx>,
+    block: Option<&'ctx BasicBlock>,
+    before: Option<&'ctx Instruction>,
+}
+
+impl<'a, 'ctx> Deref for InsertPointGuard<'a, 'ctx> {
+    type Target = Builder<'ctx>;
+
+    fn deref(&self) -> &Builder<'ctx> {
+        self.builder
+    }
+}
+
+impl<'a, 'ctx> DerefMut for InsertPointGuard<'a, 'ctx> {
+    fn deref_mut(&mut self) -> &mut Builder<'ctx> {
+        self.builder
+    }
+}
+
+impl<'a, 'ctx> Drop for InsertPointGuard<'a, 'ctx> {
+    fn drop(&mut self) {
+        match (self.block, self.before) {
+            (Some(block), Some(inst)) if inst.parent() == Some(block) => {
+                self.builder.position(block, inst)
+            }
+            (Some(block), _) => self.builder.position_at_end(block),
+            (None, _) => self.builder.clear_insertion_position(),
+        }
+    }
+}
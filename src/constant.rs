@@ -5,67 +5,295 @@ use std::mem::transmute;
 use super::*;
 
 /// Should only be used by immutable reference, `&Constant`.
-pub struct Constant(LLVMValue); // TODO: mark as unsized
-// TODO: Display and Debug
+pub struct Constant(Value);
+value_subclass!(Constant, Value);
 
-impl<'a> From<&'a Constant> for LLVMValueRef {
-    fn from(con: &'a Constant) -> LLVMValueRef {
-        unsafe { transmute::<&Constant, LLVMValueRef>(con) }
+impl Constant {
+    pub fn is_null(&self) -> bool {
+        unsafe { LLVMIsNull(self.into()) == 1 }
     }
 }
 
-impl Constant {
-    pub unsafe fn from_raw<'a>(ptr: LLVMValueRef) -> &'a Self {
-        transmute::<LLVMValueRef, &Self>(ptr)
+// Constant data:
+
+/// Integer constants
+pub struct ConstantInt(Constant);
+value_subclass!(ConstantInt, Constant);
+
+/// Floating point constants
+pub struct ConstantFP(Constant);
+value_subclass!(ConstantFP, Constant);
+
+/// The `null` pointer constant
+pub struct ConstantPointerNull(Constant);
+value_subclass!(ConstantPointerNull, Constant);
+
+/// `zeroinitializer` constants of aggregate types
+pub struct ConstantAggregateZero(Constant);
+value_subclass!(ConstantAggregateZero, Constant);
+
+/// `undef` constants
+pub struct UndefValue(Constant);
+value_subclass!(UndefValue, Constant);
+
+/// The `none` token constant
+pub struct ConstantTokenNone(Constant);
+value_subclass!(ConstantTokenNone, Constant);
+
+/// Array constants whose elements are simple data, e.g. strings
+pub struct ConstantDataArray(Constant);
+value_subclass!(ConstantDataArray, Constant);
+
+/// Vector constants whose elements are simple data
+pub struct ConstantDataVector(Constant);
+value_subclass!(ConstantDataVector, Constant);
+
+// Constant aggregates:
+
+/// Array constants
+pub struct ConstantArray(Constant);
+value_subclass!(ConstantArray, Constant);
+
+/// Structure constants
+pub struct ConstantStruct(Constant);
+value_subclass!(ConstantStruct, Constant);
+
+/// Vector constants
+pub struct ConstantVector(Constant);
+value_subclass!(ConstantVector, Constant);
+
+// Other constants:
+
+/// Constant expressions, e.g. `getelementptr` or casts of other constants
+pub struct ConstantExpr(Constant);
+value_subclass!(ConstantExpr, Constant);
+
+/// The address of a basic block, as used by `indirectbr`
+pub struct BlockAddress(Constant);
+value_subclass!(BlockAddress, Constant);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+/// LLVMLinkage, without the obsolete variants
+pub enum Linkage {
+    External = 0,
+    AvailableExternally = 1,
+    LinkOnceAny = 2,
+    LinkOnceODR = 3,
+    WeakAny = 5,
+    WeakODR = 6,
+    Appending = 7,
+    Internal = 8,
+    Private = 9,
+    ExternalWeak = 12,
+    Common = 14,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+/// LLVMVisibility
+pub enum Visibility {
+    Default = 0,
+    Hidden = 1,
+    Protected = 2,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+/// LLVMDLLStorageClass
+pub enum DLLStorageClass {
+    Default = 0,
+    DLLImport = 1,
+    DLLExport = 2,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+/// LLVMUnnamedAddr
+pub enum UnnamedAddr {
+    /// The address is significant
+    No = 0,
+    /// The address is insignificant within the module (`local_unnamed_addr`)
+    Local = 1,
+    /// The address is insignificant everywhere (`unnamed_addr`)
+    Global = 2,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+/// LLVMThreadLocalMode
+pub enum ThreadLocalMode {
+    NotThreadLocal = 0,
+    GeneralDynamic = 1,
+    LocalDynamic = 2,
+    InitialExec = 3,
+    LocalExec = 4,
+}
+
+/// The "superclass" of functions, global variables, aliases and ifuncs.
+///
+/// Though global values are constants (their address never changes), unlike
+/// other constants they have mutable properties such as linkage.
+///
+/// # Example
+///
+/// ```rust
+/// #[macro_use] extern crate llvm;
+/// use llvm::{Linkage, UnnamedAddr, Visibility};
+/// # fn main() {
+/// # let context = llvm::Context::new();
+/// # let module = context.create_module_with_name(llvmstr!("my module"));
+/// let func_type = llvm::types::Function::new(context.void_type(), &[], false);
+/// let func = module.add_function(func_type, llvmstr!("helper"));
+/// assert_eq!(func.linkage(), Linkage::External);
+///
+/// func.set_linkage(Linkage::Internal);
+/// func.set_unnamed_addr(UnnamedAddr::Global);
+/// func.set_section(llvmstr!(".text.helper"));
+/// assert_eq!(func.visibility(), Visibility::Default);
+/// assert_eq!(func.section().unwrap().to_string(), ".text.helper");
+/// # }
+/// ```
+pub struct GlobalValue(Constant);
+value_subclass!(GlobalValue, Constant);
+
+impl GlobalValue {
+    pub fn linkage(&self) -> Linkage {
+        unsafe { transmute(LLVMGetLinkage(self.into())) }
     }
 
-    pub fn is_null(&self) -> bool {
-        unsafe { LLVMIsNull(self.into()) == 1 }
+    pub fn set_linkage(&self, linkage: Linkage) {
+        unsafe { LLVMSetLinkage(self.into(), transmute(linkage)) }
+    }
+
+    pub fn visibility(&self) -> Visibility {
+        unsafe { transmute(LLVMGetVisibility(self.into())) }
+    }
+
+    /// Sets the visibility, which must be `Default` for `Internal` and
+    /// `Private` globals.
+    pub fn set_visibility(&self, visibility: Visibility) {
+        unsafe { LLVMSetVisibility(self.into(), transmute(visibility)) }
+    }
+
+    pub fn dll_storage_class(&self) -> DLLStorageClass {
+        unsafe { transmute(LLVMGetDLLStorageClass(self.into())) }
+    }
+
+    pub fn set_dll_storage_class(&self, class: DLLStorageClass) {
+        unsafe { LLVMSetDLLStorageClass(self.into(), transmute(class)) }
+    }
+
+    pub fn unnamed_addr(&self) -> UnnamedAddr {
+        unsafe { transmute(LLVMGetUnnamedAddress(self.into())) }
+    }
+
+    pub fn set_unnamed_addr(&self, unnamed_addr: UnnamedAddr) {
+        unsafe { LLVMSetUnnamedAddress(self.into(), transmute(unnamed_addr)) }
     }
 }
 
-// TODO: Many decendants of llvm::Constant don't seem to actually be the kind of
-// constant this module wraps. For example, Functions have mutable attributes
-// and such. Exactly what `Constant` represents and how to represent these other
-// types still needs to be figured out.
-/*macro_rules! subclass {
-    ($t:ty, $super:ty) => {
-        impl Deref for $t {
-            type Target = $super;
-
-            fn deref(&self) -> &Self::Target {
-                unsafe { transmute::<&Self, &Self::Target>(self) }
-            }
-        }
+/// The "superclass" of functions and global variables, which unlike aliases
+/// and ifuncs are objects of their own, with a section and an alignment.
+///
+/// # Example
+///
+/// ```rust
+/// #[macro_use] extern crate llvm;
+/// # fn main() {
+/// # let context = llvm::Context::new();
+/// # let module = context.create_module_with_name(llvmstr!("my module"));
+/// let global = module.add_global(context.i32_type(), llvmstr!("counter"));
+/// assert!(global.section().is_none());
+/// assert_eq!(global.alignment(), 0);
+///
+/// global.set_section(llvmstr!(".data.counters"));
+/// global.set_alignment(16);
+/// assert_eq!(global.section().unwrap().to_string(), ".data.counters");
+/// assert_eq!(global.alignment(), 16);
+/// assert!(global.try_as_global_object().is_some());
+/// # }
+/// ```
+pub struct GlobalObject(GlobalValue);
+value_subclass!(GlobalObject, GlobalValue);
 
-        // This would not be needed if the compiler could infer that the
-        // From<&Type> for LLVMValueRef above worked on &SubType.
-        impl<'a> From<&'a $t> for LLVMValueRef {
-            fn from(ty: &'a $t) -> LLVMValueRef {
-                unsafe { transmute::<&$t, LLVMValueRef>(ty) }
-            }
-        }
+impl GlobalObject {
+    /// Returns the section this global is placed in, or `None` if it is left
+    /// up to the backend.
+    pub fn section(&self) -> Option<&Str> {
+        let section = unsafe { LLVMGetSection(self.into()) };
 
-        impl $t {
-            pub unsafe fn from_raw<'a>(ptr: LLVMValueRef) -> &'a Self {
-                transmute::<LLVMValueRef, &Self>(ptr)
-            }
+        if section.is_null() || unsafe { *section == 0 } {
+            None
+        } else {
+            unsafe { Some(Str::from_ptr(section)) }
         }
+    }
 
-        impl fmt::Display for $t {
-            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                self.deref().fmt(f)
-            }
-        }
+    pub fn set_section<T: Borrow<Str>>(&self, section: &T) {
+        unsafe { LLVMSetSection(self.into(), section.borrow().as_ptr()) }
+    }
+
+    /// Returns the alignment in bytes, or 0 if it is left up to the backend.
+    pub fn alignment(&self) -> u32 {
+        unsafe { LLVMGetAlignment(self.into()) }
+    }
+
+    pub fn set_alignment(&self, bytes: u32) {
+        unsafe { LLVMSetAlignment(self.into(), bytes) }
+    }
+}
+
+/// Global variables
+///
+/// `&GlobalVariable`s are created with `Module::add_global`.
+pub struct GlobalVariable(GlobalObject);
+value_subclass!(GlobalVariable, GlobalObject);
 
-        impl fmt::Debug for $t {
-            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                write!(f, "llvm::{}({})", stringify!($t), self)
-            }
+impl GlobalVariable {
+    /// Returns the initial value of this global, or `None` if it is only a
+    /// declaration.
+    pub fn initializer(&self) -> Option<&Constant> {
+        let init = unsafe { LLVMGetInitializer(self.into()) };
+
+        if init.is_null() {
+            None
+        } else {
+            unsafe { Some(Constant::from_raw(init)) }
         }
     }
+
+    /// Sets the initial value of this global, turning a declaration into a
+    /// definition.
+    pub fn set_initializer(&self, init: &Constant) {
+        unsafe { LLVMSetInitializer(self.into(), init.into()) }
+    }
+
+    /// Returns whether this global is marked as `constant`, i.e. never
+    /// written to after initialization.
+    pub fn is_global_constant(&self) -> bool {
+        unsafe { LLVMIsGlobalConstant(self.into()) == 1 }
+    }
+
+    pub fn set_global_constant(&self, constant: bool) {
+        unsafe { LLVMSetGlobalConstant(self.into(), constant as LLVMBool) }
+    }
+
+    pub fn thread_local_mode(&self) -> ThreadLocalMode {
+        unsafe { transmute(LLVMGetThreadLocalMode(self.into())) }
+    }
+
+    /// Makes this global thread local using the given TLS model, or not
+    /// thread local with `ThreadLocalMode::NotThreadLocal`.
+    pub fn set_thread_local_mode(&self, mode: ThreadLocalMode) {
+        unsafe { LLVMSetThreadLocalMode(self.into(), transmute(mode)) }
+    }
 }
 
-pub struct Function(Constant);
-subclass!(Function, Constant);
-*/
+/// Global aliases
+pub struct GlobalAlias(GlobalValue);
+value_subclass!(GlobalAlias, GlobalValue);
+
+/// Indirect functions, resolved at load time
+pub struct GlobalIFunc(GlobalValue);
+value_subclass!(GlobalIFunc, GlobalValue);
@@ -1,3 +1,6 @@
+use std::mem::{transmute, ManuallyDrop};
+use std::ops::Deref;
+
 use super::*;
 
 /// A `Context` is an execution state for the core LLVM IR system. Multiple
@@ -15,26 +18,87 @@ impl Context {
         unsafe { Self::from_raw(LLVMContextCreate()) }
     }
 
-    pub fn create_builder(&mut self) -> Builder {
-        unsafe { Builder::from_raw(LLVMCreateBuilderInContext(self.as_mut())) }
+    /// Returns LLVM's global `Context`, which is used by the parts of the C
+    /// API that don't take a `Context`. It lives for the rest of the program
+    /// and is never disposed, so it's returned as a `GlobalContext`, which
+    /// derefs to `Context` but can't be turned into an owned one.
+    ///
+    /// # Safety
+    ///
+    /// Every call returns a handle to the same context, which isn't thread
+    /// safe. The caller must make sure that the global context is only used
+    /// from one thread at a time, including through other handles and by
+    /// other code in the process that uses LLVM.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// let context = unsafe { llvm::Context::global() };
+    /// assert_eq!(context.fp128_type().to_string(), "fp128");
+    /// # }
+    /// ```
+    pub unsafe fn global() -> GlobalContext {
+        GlobalContext(ManuallyDrop::new(Self::from_raw(LLVMGetGlobalContext())))
+    }
+
+    /// Creates a `Builder` for this `Context`. The `Builder` borrows the
+    /// `Context`, so the values it builds can't outlive it.
+    pub fn create_builder(&self) -> Builder {
+        unsafe { Builder::from_raw(LLVMCreateBuilderInContext(self.as_raw())) }
     }
 
-    pub fn create_module_with_name<T: Borrow<Str>>(&mut self, name: &T) -> Module {
+    pub fn create_module_with_name<T: Borrow<Str>>(&self, name: &T) -> Module {
         unsafe {
             Module::from_raw(LLVMModuleCreateWithNameInContext(
                 name.borrow().as_ptr(),
-                self.as_mut(),
+                self.as_raw(),
             ))
         }
     }
 
+    /// Creates a new basic block at the end of `func`.
+    ///
     /// Specifying a name is optional; just pass an empty string
-    pub fn append_basic_block<T: Borrow<Str>>(
-        &mut self,
-        func: LLVMValueRef,
+    pub fn append_basic_block<'a, T: Borrow<Str>>(
+        &self,
+        func: &'a Function,
         name: &T,
-    ) -> LLVMBasicBlockRef {
-        unsafe { LLVMAppendBasicBlockInContext(self.as_mut(), func, name.borrow().as_ptr()) }
+    ) -> &'a BasicBlock {
+        unsafe {
+            BasicBlock::from_raw_block(LLVMAppendBasicBlockInContext(
+                self.as_raw(),
+                func.into(),
+                name.borrow().as_ptr(),
+            ))
+        }
+    }
+
+    /// Creates an enum attribute, e.g. `nounwind`, or returns `None` if LLVM
+    /// doesn't know an attribute called `name`. `val` is only used by integer
+    /// attributes such as `align` or `dereferenceable`, and should otherwise
+    /// be 0.
+    ///
+    /// See the [LLVM Language Reference] for the available attributes.
+    ///
+    /// [LLVM Language Reference]: https://llvm.org/docs/LangRef.html#function-attributes
+    pub fn create_enum_attribute(&self, name: &str, val: u64) -> Option<&Attribute> {
+        let kind = attribute::enum_kind(name)?;
+        unsafe { Some(Attribute::from_raw(LLVMCreateEnumAttribute(self.as_raw(), kind, val))) }
+    }
+
+    /// Creates a string attribute, e.g. `"target-cpu"="x86-64"`. Unlike enum
+    /// attributes, any key is accepted.
+    pub fn create_string_attribute(&self, key: &str, value: &str) -> &Attribute {
+        unsafe {
+            Attribute::from_raw(LLVMCreateStringAttribute(
+                self.as_raw(),
+                key.as_ptr() as *const _,
+                key.len() as u32,
+                value.as_ptr() as *const _,
+                value.len() as u32,
+            ))
+        }
     }
 
     /// Returns a reference to the `types::Void` instance for an instance of
@@ -43,10 +107,56 @@ impl Context {
         unsafe { types::Void::from_raw(LLVMVoidTypeInContext(self.as_raw())) }
     }
 
+    /// Returns the 16-bit floating point type.
+    pub fn half_type(&self) -> &types::Half {
+        unsafe { types::Half::from_raw(LLVMHalfTypeInContext(self.as_raw())) }
+    }
+
+    /// Returns the 32-bit floating point type.
+    pub fn float_type(&self) -> &types::Float {
+        unsafe { types::Float::from_raw(LLVMFloatTypeInContext(self.as_raw())) }
+    }
+
     pub fn double_type(&self) -> &types::Double {
         unsafe { types::Double::from_raw(LLVMDoubleTypeInContext(self.as_raw())) }
     }
 
+    /// Returns the 80-bit x87 floating point type.
+    pub fn x86_fp80_type(&self) -> &types::X86_FP80 {
+        unsafe { types::X86_FP80::from_raw(LLVMX86FP80TypeInContext(self.as_raw())) }
+    }
+
+    /// Returns the 128-bit floating point type, with a 112-bit mantissa.
+    pub fn fp128_type(&self) -> &types::FP128 {
+        unsafe { types::FP128::from_raw(LLVMFP128TypeInContext(self.as_raw())) }
+    }
+
+    /// Returns the 128-bit PowerPC floating point type, made of two 64-bit
+    /// floats.
+    pub fn ppc_fp128_type(&self) -> &types::PPC_FP128 {
+        unsafe { types::PPC_FP128::from_raw(LLVMPPCFP128TypeInContext(self.as_raw())) }
+    }
+
+    /// Returns the type of basic block labels.
+    pub fn label_type(&self) -> &types::Label {
+        unsafe { types::Label::from_raw(LLVMLabelTypeInContext(self.as_raw())) }
+    }
+
+    pub fn metadata_type(&self) -> &types::Metadata {
+        unsafe { types::Metadata::from_raw(LLVMMetadataTypeInContext(self.as_raw())) }
+    }
+
+    /// Returns the type of the 64-bit MMX registers on x86.
+    pub fn x86_mmx_type(&self) -> &types::X86_MMX {
+        unsafe { types::X86_MMX::from_raw(LLVMX86MMXTypeInContext(self.as_raw())) }
+    }
+
+    /// Returns the type of values, such as those produced by `catchswitch`,
+    /// that can't be stored, selected or used in phis.
+    pub fn token_type(&self) -> &types::Token {
+        unsafe { types::Token::from_raw(LLVMTokenTypeInContext(self.as_raw())) }
+    }
+
     pub fn i1_type(&self) -> &types::Int {
         unsafe { types::Int::from_raw(LLVMInt1TypeInContext(self.as_raw())) }
     }
@@ -74,6 +184,35 @@ impl Context {
     pub fn integer_type(&self, num_bits: u32) -> &types::Int {
         unsafe { types::Int::from_raw(LLVMIntTypeInContext(self.as_raw(), num_bits)) }
     }
+
+    /// Returns the literal struct with the given element types, e.g.
+    /// `{ i32, double }`, or `<{ i32, double }>` if `packed` is true.
+    pub fn struct_type<'a>(
+        &'a self,
+        element_types: &[&'a Type],
+        packed: bool,
+    ) -> &'a types::Struct {
+        unsafe {
+            types::Struct::from_raw(LLVMStructTypeInContext(
+                self.as_raw(),
+                transmute::<*const &Type, *mut LLVMTypeRef>(element_types.as_ptr()),
+                element_types.len() as u32,
+                packed as LLVMBool,
+            ))
+        }
+    }
+
+    /// Creates an opaque identified struct, whose body can be set with
+    /// `types::Struct::set_body`. If `name` is already taken, a suffix is
+    /// added to make it unique.
+    pub fn named_struct<T: Borrow<Str>>(&self, name: &T) -> &types::Struct {
+        unsafe {
+            types::Struct::from_raw(LLVMStructCreateNamed(
+                self.as_raw(),
+                name.borrow().as_ptr(),
+            ))
+        }
+    }
 }
 
 impl Drop for Context {
@@ -83,3 +222,16 @@ impl Drop for Context {
         }
     }
 }
+
+/// A handle to LLVM's global `Context`, returned by `Context::global`.
+/// Dropping it leaves the global context alone.
+#[derive(Debug)]
+pub struct GlobalContext(ManuallyDrop<Context>);
+
+impl Deref for GlobalContext {
+    type Target = Context;
+
+    fn deref(&self) -> &Context {
+        &self.0
+    }
+}
@@ -1,58 +1,261 @@
-use llvm_sys::prelude::*;
-use llvm_sys::core as llvm;
+//! Functions
 
-#[derive(Debug)]
-pub struct Function {
-    pub ptr: LLVMValueRef,
+use super::*;
+
+/// LLVMCallConv
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub enum CallConv {
+    C = 0,
+    Fast = 8,
+    Cold = 9,
+    GHC = 10,
+    HiPE = 11,
+    WebKitJS = 12,
+    AnyReg = 13,
+    PreserveMost = 14,
+    PreserveAll = 15,
+    Swift = 16,
+    CXXFastTLS = 17,
+    X86Stdcall = 64,
+    X86Fastcall = 65,
+    ARMAPCS = 66,
+    ARMAAPCS = 67,
+    ARMAAPCSVFP = 68,
+    MSP430Intr = 69,
+    X86ThisCall = 70,
+    PTXKernel = 71,
+    PTXDevice = 72,
+    SPIRFunc = 75,
+    SPIRKernel = 76,
+    IntelOCLBI = 77,
+    X86_64SysV = 78,
+    Win64 = 79,
+    X86VectorCall = 80,
+    HHVM = 81,
+    HHVMC = 82,
+    X86Intr = 83,
+    AVRIntr = 84,
+    AVRSignal = 85,
+    AVRBuiltin = 86,
+    AMDGPUVS = 87,
+    AMDGPUGS = 88,
+    AMDGPUPS = 89,
+    AMDGPUCS = 90,
+    AMDGPUKernel = 91,
+    X86RegCall = 92,
+    AMDGPUHS = 93,
+    MSP430Builtin = 94,
+}
+
+impl CallConv {
+    // Newer versions of LLVM have more calling conventions, so the IDs it
+    // returns can't simply be transmuted.
+    pub(crate) fn from_id(id: u32) -> Option<CallConv> {
+        use self::CallConv::*;
+
+        [
+            C, Fast, Cold, GHC, HiPE, WebKitJS, AnyReg, PreserveMost, PreserveAll, Swift,
+            CXXFastTLS, X86Stdcall, X86Fastcall, ARMAPCS, ARMAAPCS, ARMAAPCSVFP, MSP430Intr,
+            X86ThisCall, PTXKernel, PTXDevice, SPIRFunc, SPIRKernel, IntelOCLBI, X86_64SysV,
+            Win64, X86VectorCall, HHVM, HHVMC, X86Intr, AVRIntr, AVRSignal, AVRBuiltin,
+            AMDGPUVS, AMDGPUGS, AMDGPUPS, AMDGPUCS, AMDGPUKernel, X86RegCall, AMDGPUHS,
+            MSP430Builtin,
+        ]
+            .iter()
+            .cloned()
+            .find(|&cc| cc as u32 == id)
+    }
 }
 
+/// A function definition or declaration in a `Module`.
+///
+/// `&Function`s are created with `Module::add_function`, and implicitly
+/// upcast into `&Value`s where needed, e.g. when passed to
+/// `Builder::build_call`.
+pub struct Function(GlobalObject);
+value_subclass!(Function, GlobalObject);
+
 impl Function {
-    pub fn from_value_ref(p: LLVMValueRef) -> Function {
-        Function { ptr: p }
+    /// Returns the type of this function. Note that `get_type` returns the
+    /// type of the function as a value, which is a pointer to this type.
+    pub fn function_type(&self) -> &types::Function {
+        unsafe { types::Function::from_raw(LLVMGetElementType(LLVMTypeOf(self.into()))) }
+    }
+
+    pub fn return_type(&self) -> &Type {
+        self.function_type().return_type()
     }
 
+    pub fn param_count(&self) -> u32 {
+        unsafe { LLVMCountParams(self.into()) }
+    }
+
+    /// Returns an iterator over the parameters of this function.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i64_type = context.i64_type();
+    /// let func_type = llvm::types::Function::new(i64_type, &[i64_type, i64_type], false);
+    /// let func = module.add_function(func_type, llvmstr!("add"));
+    ///
+    /// for (param, name) in func.params().zip(&[llvmstr!("x"), llvmstr!("y")]) {
+    ///     param.set_name(name);
+    /// }
+    /// assert_eq!(func.get_param(1).unwrap().name().to_string(), "y");
+    /// # }
+    /// ```
     pub fn params(&self) -> FunctionParamIter {
         FunctionParamIter {
-            arg: self.ptr,
-            first: true,
+            func: self.into(),
+            arg: None,
         }
     }
 
     // TODO: Check if there is an optimization so that we could
     // call func.params().nth(1) and call this function
-    pub fn get_param(&self, index: u32) -> Option<LLVMValueRef> {
-        let p = unsafe { llvm::LLVMGetParam(self.ptr, index) };
+    pub fn get_param(&self, index: u32) -> Option<&Argument> {
+        if index < self.param_count() {
+            unsafe { Some(Argument::from_raw(LLVMGetParam(self.into(), index))) }
+        } else {
+            None
+        }
+    }
 
-        if p.is_null() {
-            return None;
+    pub fn count_basic_blocks(&self) -> u32 {
+        unsafe { LLVMCountBasicBlocks(self.into()) }
+    }
+
+    /// Returns an iterator over the basic blocks of this function, in order.
+    pub fn basic_blocks(&self) -> BasicBlockIter {
+        BasicBlockIter {
+            func: self.into(),
+            block: None,
+        }
+    }
+
+    /// Returns the entry block of this function, or `None` if this function
+    /// is only a declaration.
+    pub fn entry_block(&self) -> Option<&BasicBlock> {
+        if self.count_basic_blocks() == 0 {
+            None
         } else {
-            return Some(p);
+            unsafe { Some(BasicBlock::from_raw_block(LLVMGetEntryBasicBlock(self.into()))) }
+        }
+    }
+
+    /// Returns the calling convention of this function, or `None` if it is
+    /// one that was added to LLVM after `CallConv`.
+    pub fn call_conv(&self) -> Option<CallConv> {
+        CallConv::from_id(unsafe { LLVMGetFunctionCallConv(self.into()) })
+    }
+
+    /// Sets the calling convention of this function. Calls to it should use
+    /// the same calling convention; see `CallSite::set_call_conv`.
+    pub fn set_call_conv(&self, call_conv: CallConv) {
+        unsafe { LLVMSetFunctionCallConv(self.into(), call_conv as u32) }
+    }
+
+    /// Returns the personality function used to handle exceptions thrown
+    /// within this function, if one has been set.
+    pub fn personality_fn(&self) -> Option<&Value> {
+        unsafe {
+            if LLVMHasPersonalityFn(self.into()) == 1 {
+                Some(Value::from_raw(LLVMGetPersonalityFn(self.into())))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Sets the personality function, which is required for this function to
+    /// contain any exception handling instructions, e.g. `__gxx_personality_v0`
+    /// for the Itanium C++ ABI.
+    pub fn set_personality_fn(&self, personality: &Value) {
+        unsafe { LLVMSetPersonalityFn(self.into(), personality.into()) }
+    }
+
+    /// Runs the LLVM verifier on this function, returning true if it is well
+    /// formed.
+    pub fn verify(&self) -> bool {
+        unsafe {
+            LLVMVerifyFunction(
+                self.into(),
+                LLVMVerifierFailureAction::LLVMReturnStatusAction,
+            ) == 0
         }
     }
+
+    /// Removes this function from its module and deletes it. Marked as
+    /// `unsafe` because any remaining references to the function, its
+    /// arguments, or its basic blocks are left dangling.
+    pub unsafe fn delete(&self) {
+        LLVMDeleteFunction(self.into());
+    }
 }
 
+// The attributes of the function itself, its return value and its parameters
+// are distinguished by an `AttributeIndex`.
+attribute_fns!(
+    Function,
+    LLVMAddAttributeAtIndex,
+    LLVMGetAttributeCountAtIndex,
+    LLVMGetAttributesAtIndex,
+    LLVMGetEnumAttributeAtIndex,
+    LLVMGetStringAttributeAtIndex,
+    LLVMRemoveEnumAttributeAtIndex,
+    LLVMRemoveStringAttributeAtIndex
+);
 
 #[derive(Debug)]
-pub struct FunctionParamIter {
-    arg: LLVMValueRef,
-    first: bool,
+pub struct FunctionParamIter<'a> {
+    func: LLVMValueRef,
+    arg: Option<&'a Argument>,
 }
 
-// TODO: Needs testing
-impl Iterator for FunctionParamIter {
-    type Item = LLVMValueRef;
+impl<'a> Iterator for FunctionParamIter<'a> {
+    type Item = &'a Argument;
 
-    fn next(&mut self) -> Option<LLVMValueRef> {
-        self.arg = if self.first {
-            unsafe { llvm::LLVMGetFirstParam(self.arg) }
+    fn next(&mut self) -> Option<&'a Argument> {
+        let next = match self.arg {
+            None => unsafe { LLVMGetFirstParam(self.func) },
+            Some(arg) => unsafe { LLVMGetNextParam(arg.into()) },
+        };
+
+        if next.is_null() {
+            None
         } else {
-            unsafe { llvm::LLVMGetNextParam(self.arg) }
+            self.arg = unsafe { Some(Argument::from_raw(next)) };
+            self.arg
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BasicBlockIter<'a> {
+    func: LLVMValueRef,
+    block: Option<&'a BasicBlock>,
+}
+
+impl<'a> Iterator for BasicBlockIter<'a> {
+    type Item = &'a BasicBlock;
+
+    fn next(&mut self) -> Option<&'a BasicBlock> {
+        let next = match self.block {
+            None => unsafe { LLVMGetFirstBasicBlock(self.func) },
+            Some(block) => unsafe { LLVMGetNextBasicBlock(block.into()) },
         };
 
-        if self.arg.is_null() {
+        if next.is_null() {
             None
         } else {
-            Some(self.arg)
+            self.block = unsafe { Some(BasicBlock::from_raw_block(next)) };
+            self.block
         }
     }
 }
@@ -0,0 +1,953 @@
+//! Instructions
+
+use std::mem;
+use std::ptr;
+
+use super::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+/// LLVMOpcode
+pub enum Opcode {
+    // Terminators
+    Ret = 1,
+    Br = 2,
+    Switch = 3,
+    IndirectBr = 4,
+    Invoke = 5,
+    Unreachable = 7,
+    Resume = 58,
+    CleanupRet = 61,
+    CatchRet = 62,
+    CatchSwitch = 65,
+
+    // Binary operators
+    Add = 8,
+    FAdd = 9,
+    Sub = 10,
+    FSub = 11,
+    Mul = 12,
+    FMul = 13,
+    UDiv = 14,
+    SDiv = 15,
+    FDiv = 16,
+    URem = 17,
+    SRem = 18,
+    FRem = 19,
+
+    // Logical operators
+    Shl = 20,
+    LShr = 21,
+    AShr = 22,
+    And = 23,
+    Or = 24,
+    Xor = 25,
+
+    // Memory operators
+    Alloca = 26,
+    Load = 27,
+    Store = 28,
+    GetElementPtr = 29,
+
+    // Cast operators
+    Trunc = 30,
+    ZExt = 31,
+    SExt = 32,
+    FPToUI = 33,
+    FPToSI = 34,
+    UIToFP = 35,
+    SIToFP = 36,
+    FPTrunc = 37,
+    FPExt = 38,
+    PtrToInt = 39,
+    IntToPtr = 40,
+    BitCast = 41,
+    AddrSpaceCast = 60,
+
+    // Other operators
+    ICmp = 42,
+    FCmp = 43,
+    Phi = 44,
+    Call = 45,
+    Select = 46,
+    UserOp1 = 47,
+    UserOp2 = 48,
+    VAArg = 49,
+    ExtractElement = 50,
+    InsertElement = 51,
+    ShuffleVector = 52,
+    ExtractValue = 53,
+    InsertValue = 54,
+
+    // Atomic operators
+    Fence = 55,
+    AtomicCmpXchg = 56,
+    AtomicRMW = 57,
+
+    // Exception handling operators
+    LandingPad = 59,
+    CatchPad = 63,
+    CleanupPad = 64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+/// LLVMAtomicOrdering
+pub enum AtomicOrdering {
+    NotAtomic = 0,
+    Unordered = 1,
+    Monotonic = 2,
+    Acquire = 4,
+    Release = 5,
+    AcquireRelease = 6,
+    SequentiallyConsistent = 7,
+}
+
+impl From<AtomicOrdering> for LLVMAtomicOrdering {
+    fn from(ordering: AtomicOrdering) -> LLVMAtomicOrdering {
+        match ordering {
+            AtomicOrdering::NotAtomic => LLVMAtomicOrdering::LLVMAtomicOrderingNotAtomic,
+            AtomicOrdering::Unordered => LLVMAtomicOrdering::LLVMAtomicOrderingUnordered,
+            AtomicOrdering::Monotonic => LLVMAtomicOrdering::LLVMAtomicOrderingMonotonic,
+            AtomicOrdering::Acquire => LLVMAtomicOrdering::LLVMAtomicOrderingAcquire,
+            AtomicOrdering::Release => LLVMAtomicOrdering::LLVMAtomicOrderingRelease,
+            AtomicOrdering::AcquireRelease => LLVMAtomicOrdering::LLVMAtomicOrderingAcquireRelease,
+            AtomicOrdering::SequentiallyConsistent => {
+                LLVMAtomicOrdering::LLVMAtomicOrderingSequentiallyConsistent
+            }
+        }
+    }
+}
+
+// Not bound by llvm-sys, and possibly newer than LLVM 7, so these are only
+// declared when asked for; an LLVM without them would fail to link.
+#[cfg(feature = "weak-cmpxchg")]
+extern "C" {
+    fn LLVMGetWeak(CmpXchgInst: LLVMValueRef) -> LLVMBool;
+    fn LLVMSetWeak(CmpXchgInst: LLVMValueRef, IsWeak: LLVMBool);
+}
+
+/// Enumeration of the instructions that have their own wrappers. Used for
+/// safe downcasting of `Instruction`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InstructionKind<'a> {
+    Phi(&'a Phi),
+    Switch(&'a Switch),
+    IndirectBr(&'a IndirectBr),
+    LandingPad(&'a LandingPad),
+    CatchSwitch(&'a CatchSwitch),
+    /// A `call` or `invoke`
+    CallSite(&'a CallSite),
+    /// Any other instruction; `Instruction::opcode` tells which one.
+    Other(&'a Instruction),
+}
+
+macro_rules! try_as_instruction_fns {
+    ($(pub fn $name:ident -> $variant:tt)*) => {
+        $(
+            /// Attempt a downcast, returning `None` if `self` isn't that kind
+            /// of instruction.
+            pub fn $name<'a>(&'a self) -> Option<&'a $variant> {
+                if let InstructionKind::$variant(i) = self.downcast() {
+                    Some(i)
+                } else {
+                    None
+                }
+            }
+        )*
+    }
+}
+
+/// The "superclass" of all instructions.
+///
+/// `&Instruction`s are usually obtained by downcasting the `&Value`s returned
+/// by `Builder`, or by iterating over the instructions of a `BasicBlock`.
+pub struct Instruction(Value);
+value_subclass!(Instruction, Value);
+
+impl Instruction {
+    pub fn opcode(&self) -> Opcode {
+        unsafe { mem::transmute(LLVMGetInstructionOpcode(self.into())) }
+    }
+
+    /// Downcasts an `&Instruction` to one of the instruction wrappers of this
+    /// crate, or `InstructionKind::Other` for instructions that don't have
+    /// one. This is the instruction counterpart of `Value::downcast`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// use llvm::InstructionKind;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// # let func_type = llvm::types::Function::new(context.void_type(), &[], false);
+    /// # let func = module.add_function(func_type, llvmstr!("f"));
+    /// # let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// # let mut builder = context.create_builder();
+    /// # builder.position_at_end(entry);
+    /// let call = builder.build_call(func, &[], llvmstr!(""));
+    /// builder.build_ret_void();
+    ///
+    /// for inst in entry.instructions() {
+    ///     match inst.downcast() {
+    ///         InstructionKind::CallSite(c) => assert_eq!(c, call),
+    ///         InstructionKind::Other(i) => assert_eq!(i.opcode(), llvm::Opcode::Ret),
+    ///         _ => unreachable!(),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn downcast(&self) -> InstructionKind {
+        unsafe {
+            match self.opcode() {
+                Opcode::Phi => InstructionKind::Phi(mem::transmute(self)),
+                Opcode::Switch => InstructionKind::Switch(mem::transmute(self)),
+                Opcode::IndirectBr => InstructionKind::IndirectBr(mem::transmute(self)),
+                Opcode::LandingPad => InstructionKind::LandingPad(mem::transmute(self)),
+                Opcode::CatchSwitch => InstructionKind::CatchSwitch(mem::transmute(self)),
+                Opcode::Call | Opcode::Invoke => InstructionKind::CallSite(mem::transmute(self)),
+                _ => InstructionKind::Other(self),
+            }
+        }
+    }
+
+    try_as_instruction_fns! {
+        pub fn try_as_phi -> Phi
+        pub fn try_as_switch -> Switch
+        pub fn try_as_indirect_br -> IndirectBr
+        pub fn try_as_landing_pad -> LandingPad
+        pub fn try_as_catch_switch -> CatchSwitch
+        pub fn try_as_call_site -> CallSite
+    }
+
+    pub fn num_operands(&self) -> u32 {
+        unsafe { LLVMGetNumOperands(self.into()) as u32 }
+    }
+
+    /// Returns the operand at `index`, or `None` if `index` is out of bounds.
+    /// Operands that are basic blocks, e.g. the destinations of a `br`, can be
+    /// downcast with `Value::try_as_basic_block`.
+    pub fn operand(&self, index: u32) -> Option<&Value> {
+        if index < self.num_operands() {
+            unsafe { Some(Value::from_raw(LLVMGetOperand(self.into(), index))) }
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over the operands of this instruction.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i32_type = context.i32_type();
+    /// let func_type = llvm::types::Function::new(i32_type, &[i32_type], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// let x = func.get_param(0).unwrap();
+    /// x.set_name(llvmstr!("x"));
+    ///
+    /// let mut builder = context.create_builder();
+    /// builder.position_at_end(entry);
+    /// let one = i32_type.constant(1);
+    /// let sum = builder.build_add(x, one, llvmstr!("sum")).try_as_instruction().unwrap();
+    ///
+    /// assert_eq!(sum.num_operands(), 2);
+    /// assert_eq!(sum.operands().collect::<Vec<_>>(), vec![&**x, &**one]);
+    /// assert_eq!(sum.operand(1), Some(&**one));
+    /// assert_eq!(sum.operand(2), None);
+    ///
+    /// sum.set_operand(1, x);
+    /// assert_eq!(sum.to_string(), "  %sum = add i32 %x, %x");
+    /// # }
+    /// ```
+    pub fn operands(&self) -> OperandIter {
+        OperandIter {
+            inst: self,
+            index: 0,
+        }
+    }
+
+    /// Returns the use of the operand at `index`, or `None` if `index` is out
+    /// of bounds.
+    pub fn operand_use(&self, index: u32) -> Option<&Use> {
+        if index < self.num_operands() {
+            unsafe { Some(Use::from_raw(LLVMGetOperandUse(self.into(), index))) }
+        } else {
+            None
+        }
+    }
+
+    /// Replaces the operand at `index` with `val`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_operand(&self, index: u32, val: &Value) {
+        assert!(
+            index < self.num_operands(),
+            "operand index {} out of bounds for {}",
+            index,
+            self
+        );
+        unsafe { LLVMSetOperand(self.into(), index, val.into()) }
+    }
+
+    /// Returns the predicate of an `icmp` instruction, or `None` if this is
+    /// some other instruction.
+    pub fn icmp_predicate(&self) -> Option<IntPredicate> {
+        if self.opcode() == Opcode::ICmp {
+            unsafe { Some(mem::transmute(LLVMGetICmpPredicate(self.into()))) }
+        } else {
+            None
+        }
+    }
+
+    /// Returns the predicate of an `fcmp` instruction, or `None` if this is
+    /// some other instruction.
+    pub fn fcmp_predicate(&self) -> Option<RealPredicate> {
+        if self.opcode() == Opcode::FCmp {
+            unsafe { Some(mem::transmute(LLVMGetFCmpPredicate(self.into()))) }
+        } else {
+            None
+        }
+    }
+
+    /// Returns the basic block this instruction belongs to, or `None` if it
+    /// has been removed from its block.
+    pub fn parent(&self) -> Option<&BasicBlock> {
+        let block = unsafe { LLVMGetInstructionParent(self.into()) };
+
+        if block.is_null() {
+            None
+        } else {
+            unsafe { Some(BasicBlock::from_raw_block(block)) }
+        }
+    }
+
+    pub fn next_instruction(&self) -> Option<&Instruction> {
+        let inst = unsafe { LLVMGetNextInstruction(self.into()) };
+
+        if inst.is_null() {
+            None
+        } else {
+            unsafe { Some(Instruction::from_raw(inst)) }
+        }
+    }
+
+    pub fn previous_instruction(&self) -> Option<&Instruction> {
+        let inst = unsafe { LLVMGetPreviousInstruction(self.into()) };
+
+        if inst.is_null() {
+            None
+        } else {
+            unsafe { Some(Instruction::from_raw(inst)) }
+        }
+    }
+
+    /// Removes this instruction from its basic block without deleting it.
+    /// It can be inserted again with `Builder::insert`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i32_type = context.i32_type();
+    /// let func_type = llvm::types::Function::new(i32_type, &[i32_type], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// let x = func.get_param(0).unwrap();
+    /// x.set_name(llvmstr!("x"));
+    ///
+    /// let mut builder = context.create_builder();
+    /// builder.position_at_end(entry);
+    /// let sum = builder.build_add(x, x, llvmstr!("sum")).try_as_instruction().unwrap();
+    /// let ret = builder.build_ret(sum).try_as_instruction().unwrap();
+    ///
+    /// sum.remove_from_parent();
+    /// assert_eq!(sum.parent(), None);
+    /// assert_eq!(entry.instructions().collect::<Vec<_>>(), vec![ret]);
+    ///
+    /// builder.position_before(ret);
+    /// builder.insert(sum);
+    /// assert_eq!(sum.parent(), Some(entry));
+    /// assert!(func.verify());
+    /// # }
+    /// ```
+    pub fn remove_from_parent(&self) {
+        unsafe { LLVMInstructionRemoveFromParent(self.into()) }
+    }
+
+    /// Removes this instruction from its basic block and deletes it. Marked
+    /// as `unsafe` because any remaining references to the instruction are
+    /// left dangling.
+    pub unsafe fn erase_from_parent(&self) {
+        LLVMInstructionEraseFromParent(self.into());
+    }
+
+    /// Creates a copy of this instruction that has no name and doesn't belong
+    /// to any basic block.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i32_type = context.i32_type();
+    /// let func_type = llvm::types::Function::new(i32_type, &[i32_type], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// let x = func.get_param(0).unwrap();
+    /// x.set_name(llvmstr!("x"));
+    ///
+    /// let mut builder = context.create_builder();
+    /// builder.position_at_end(entry);
+    /// let sum = builder.build_add(x, x, llvmstr!("sum")).try_as_instruction().unwrap();
+    ///
+    /// let copy = sum.clone_instruction();
+    /// assert_ne!(copy, sum);
+    /// assert_eq!(copy.parent(), None);
+    /// assert_eq!(copy.operands().collect::<Vec<_>>(), sum.operands().collect::<Vec<_>>());
+    ///
+    /// builder.insert_with_name(copy, llvmstr!("copy"));
+    /// assert_eq!(copy.to_string(), "  %copy = add i32 %x, %x");
+    /// # }
+    /// ```
+    pub fn clone_instruction(&self) -> &Instruction {
+        unsafe { Instruction::from_raw(LLVMInstructionClone(self.into())) }
+    }
+
+    // TODO: accessors for the `nsw`, `nuw` and `exact` flags need LLVMGetNSW,
+    // LLVMSetNSW and friends, which the C API only has since LLVM 17. Until
+    // then, the flags can only be set when building, with `build_nswadd` etc.
+
+    fn is_memory_access(&self) -> bool {
+        matches!(self.opcode(), Opcode::Load | Opcode::Store)
+    }
+
+    /// Returns the alignment of an `alloca`, `load` or `store` instruction, or
+    /// `None` for any other instruction.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i32_type = context.i32_type();
+    /// let func_type = llvm::types::Function::new(i32_type, &[i32_type], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// let x = func.get_param(0).unwrap();
+    /// x.set_name(llvmstr!("x"));
+    ///
+    /// let mut builder = context.create_builder();
+    /// builder.position_at_end(entry);
+    /// let slot = builder.build_alloca(i32_type, llvmstr!("slot"));
+    /// let store = builder.build_store(x, slot);
+    ///
+    /// slot.set_alignment(8);
+    /// store.set_alignment(4);
+    /// assert_eq!(slot.alignment(), Some(8));
+    /// assert_eq!(store.alignment(), Some(4));
+    /// assert_eq!(store.to_string(), "  store i32 %x, i32* %slot, align 4");
+    ///
+    /// let ret = builder.build_ret(x).try_as_instruction().unwrap();
+    /// assert_eq!(ret.alignment(), None);
+    /// # }
+    /// ```
+    pub fn alignment(&self) -> Option<u32> {
+        if self.opcode() == Opcode::Alloca || self.is_memory_access() {
+            unsafe { Some(LLVMGetAlignment(self.into())) }
+        } else {
+            None
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if this isn't an `alloca`, `load` or `store` instruction.
+    pub fn set_alignment(&self, bytes: u32) {
+        assert!(
+            self.opcode() == Opcode::Alloca || self.is_memory_access(),
+            "cannot set the alignment of {}",
+            self
+        );
+        unsafe { LLVMSetAlignment(self.into(), bytes) }
+    }
+
+    /// Returns whether a `load` or `store` instruction is volatile, or `None`
+    /// for any other instruction.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i32_type = context.i32_type();
+    /// let func_type = llvm::types::Function::new(i32_type, &[i32_type], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// let x = func.get_param(0).unwrap();
+    /// x.set_name(llvmstr!("x"));
+    ///
+    /// let mut builder = context.create_builder();
+    /// builder.position_at_end(entry);
+    /// let slot = builder.build_alloca(i32_type, llvmstr!("slot"));
+    /// let load = builder.build_load(slot, llvmstr!("val"));
+    ///
+    /// assert_eq!(load.is_volatile(), Some(false));
+    /// load.set_volatile(true);
+    /// assert_eq!(load.is_volatile(), Some(true));
+    /// assert!(load.to_string().starts_with("  %val = load volatile i32, i32* %slot"));
+    /// assert_eq!(slot.is_volatile(), None);
+    /// # }
+    /// ```
+    pub fn is_volatile(&self) -> Option<bool> {
+        if self.is_memory_access() {
+            unsafe { Some(LLVMGetVolatile(self.into()) == 1) }
+        } else {
+            None
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if this isn't a `load` or `store` instruction.
+    pub fn set_volatile(&self, volatile: bool) {
+        assert!(self.is_memory_access(), "cannot set {} as volatile", self);
+        unsafe { LLVMSetVolatile(self.into(), volatile as LLVMBool) }
+    }
+
+    /// Returns the atomic ordering of a `load` or `store` instruction, or
+    /// `None` for any other instruction.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use llvm::AtomicOrdering;
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i32_type = context.i32_type();
+    /// let func_type = llvm::types::Function::new(i32_type, &[i32_type], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let entry = context.append_basic_block(func, llvmstr!("entry"));
+    /// let x = func.get_param(0).unwrap();
+    /// x.set_name(llvmstr!("x"));
+    ///
+    /// let mut builder = context.create_builder();
+    /// builder.position_at_end(entry);
+    /// let slot = builder.build_alloca(i32_type, llvmstr!("slot"));
+    /// let store = builder.build_store(x, slot);
+    ///
+    /// assert_eq!(store.ordering(), Some(AtomicOrdering::NotAtomic));
+    /// store.set_alignment(4);
+    /// store.set_ordering(AtomicOrdering::Release);
+    /// assert_eq!(store.ordering(), Some(AtomicOrdering::Release));
+    /// assert_eq!(store.to_string(), "  store atomic i32 %x, i32* %slot release, align 4");
+    /// assert_eq!(slot.ordering(), None);
+    /// # }
+    /// ```
+    pub fn ordering(&self) -> Option<AtomicOrdering> {
+        if self.is_memory_access() {
+            unsafe { Some(mem::transmute(LLVMGetOrdering(self.into()))) }
+        } else {
+            None
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if this isn't a `load` or `store` instruction.
+    pub fn set_ordering(&self, ordering: AtomicOrdering) {
+        assert!(
+            self.is_memory_access(),
+            "cannot set the atomic ordering of {}",
+            self
+        );
+        unsafe { LLVMSetOrdering(self.into(), ordering.into()) }
+    }
+
+    /// Returns whether a `cmpxchg` instruction is weak, i.e. allowed to fail
+    /// spuriously, or `None` for any other instruction.
+    ///
+    /// Needs the `weak-cmpxchg` feature.
+    #[cfg(feature = "weak-cmpxchg")]
+    pub fn is_weak(&self) -> Option<bool> {
+        if self.opcode() == Opcode::AtomicCmpXchg {
+            unsafe { Some(LLVMGetWeak(self.into()) == 1) }
+        } else {
+            None
+        }
+    }
+
+    /// Marks a `cmpxchg` instruction as weak, so that it can fail spuriously,
+    /// e.g. in a retry loop. Needs the `weak-cmpxchg` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this isn't a `cmpxchg` instruction.
+    #[cfg(feature = "weak-cmpxchg")]
+    pub fn set_weak(&self, weak: bool) {
+        assert!(
+            self.opcode() == Opcode::AtomicCmpXchg,
+            "cannot set {} as weak",
+            self
+        );
+        unsafe { LLVMSetWeak(self.into(), weak as LLVMBool) }
+    }
+}
+
+#[derive(Debug)]
+pub struct OperandIter<'a> {
+    inst: &'a Instruction,
+    index: u32,
+}
+
+impl<'a> Iterator for OperandIter<'a> {
+    type Item = &'a Value;
+
+    fn next(&mut self) -> Option<&'a Value> {
+        let operand = self.inst.operand(self.index);
+        if operand.is_some() {
+            self.index += 1;
+        }
+        operand
+    }
+}
+
+/// A `phi` instruction, which selects a value depending on the predecessor
+/// block that control flow came from.
+///
+/// `&Phi`s are created with `Builder::build_phi`, or by downcasting with
+/// `Instruction::try_as_phi`.
+///
+/// # Example
+///
+/// ```rust
+/// #[macro_use] extern crate llvm;
+/// # fn main() {
+/// # let context = llvm::Context::new();
+/// # let module = context.create_module_with_name(llvmstr!("my module"));
+/// let i32_type = context.i32_type();
+/// let func_type = llvm::types::Function::new(i32_type, &[context.i1_type()], false);
+/// let func = module.add_function(func_type, llvmstr!("choose"));
+/// let entry = context.append_basic_block(func, llvmstr!("entry"));
+/// let then = context.append_basic_block(func, llvmstr!("then"));
+/// let join = context.append_basic_block(func, llvmstr!("join"));
+///
+/// let mut builder = context.create_builder();
+/// builder.position_at_end(entry);
+/// builder.build_cond_br(func.get_param(0).unwrap(), then, join);
+/// builder.position_at_end(then);
+/// builder.build_br(join);
+///
+/// builder.position_at_end(join);
+/// let phi = builder.build_phi(i32_type, llvmstr!("x"));
+/// phi.add_incoming(&[(i32_type.constant(1), entry), (i32_type.constant(2), then)]);
+/// builder.build_ret(phi);
+///
+/// assert_eq!(phi.count_incoming(), 2);
+/// assert!(phi.incoming().any(|(_, block)| block == then));
+/// assert!(func.verify());
+/// # }
+/// ```
+pub struct Phi(Instruction);
+value_subclass!(Phi, Instruction);
+
+impl Phi {
+    /// Adds an incoming value for each `(value, block)` pair, where `value`
+    /// is selected if control flow came from `block`.
+    pub fn add_incoming(&self, incoming: &[(&Value, &BasicBlock)]) {
+        let (mut values, mut blocks): (Vec<LLVMValueRef>, Vec<LLVMBasicBlockRef>) = incoming
+            .iter()
+            .map(|&(value, block)| (LLVMValueRef::from(value), LLVMBasicBlockRef::from(block)))
+            .unzip();
+
+        unsafe {
+            LLVMAddIncoming(
+                self.into(),
+                values.as_mut_ptr(),
+                blocks.as_mut_ptr(),
+                incoming.len() as u32,
+            )
+        }
+    }
+
+    pub fn count_incoming(&self) -> u32 {
+        unsafe { LLVMCountIncoming(self.into()) }
+    }
+
+    /// Returns the incoming `(value, block)` pair at `index`, or `None` if
+    /// `index` is out of bounds.
+    pub fn get_incoming(&self, index: u32) -> Option<(&Value, &BasicBlock)> {
+        if index < self.count_incoming() {
+            unsafe {
+                Some((
+                    Value::from_raw(LLVMGetIncomingValue(self.into(), index)),
+                    BasicBlock::from_raw_block(LLVMGetIncomingBlock(self.into(), index)),
+                ))
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over the incoming `(value, block)` pairs of this
+    /// `phi`.
+    pub fn incoming(&self) -> IncomingIter {
+        IncomingIter {
+            phi: self,
+            index: 0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct IncomingIter<'a> {
+    phi: &'a Phi,
+    index: u32,
+}
+
+impl<'a> Iterator for IncomingIter<'a> {
+    type Item = (&'a Value, &'a BasicBlock);
+
+    fn next(&mut self) -> Option<(&'a Value, &'a BasicBlock)> {
+        let incoming = self.phi.get_incoming(self.index);
+        if incoming.is_some() {
+            self.index += 1;
+        }
+        incoming
+    }
+}
+
+/// A `switch` instruction, which branches to the case whose value equals
+/// its condition, or to its default destination if there is none.
+///
+/// `&Switch`es are created with `Builder::build_switch`, or by downcasting
+/// with `Instruction::try_as_switch`.
+pub struct Switch(Instruction);
+value_subclass!(Switch, Instruction);
+
+impl Switch {
+    /// Adds a case that branches to `dest` if the condition equals `val`,
+    /// which must be an integer constant of the same type as the condition.
+    pub fn add_case(&self, val: &Constant, dest: &BasicBlock) {
+        unsafe { LLVMAddCase(self.into(), val.into(), dest.into()) }
+    }
+
+    /// Returns the block branched to if no case matches.
+    pub fn default_dest(&self) -> &BasicBlock {
+        unsafe { BasicBlock::from_raw_block(LLVMGetSwitchDefaultDest(self.into())) }
+    }
+}
+
+/// An `indirectbr` instruction, which branches to an address taken with a
+/// `blockaddress` constant.
+///
+/// `&IndirectBr`s are created with `Builder::build_indirect_br`, or by
+/// downcasting with `Instruction::try_as_indirect_br`.
+pub struct IndirectBr(Instruction);
+value_subclass!(IndirectBr, Instruction);
+
+impl IndirectBr {
+    /// Adds `dest` to the blocks this instruction may branch to. Every block
+    /// whose address can be branched to must be added.
+    pub fn add_destination(&self, dest: &BasicBlock) {
+        unsafe { LLVMAddDestination(self.into(), dest.into()) }
+    }
+}
+
+/// A `landingpad` instruction, which receives an exception thrown by an
+/// `invoke`d function when using an Itanium-style personality function.
+///
+/// `&LandingPad`s are created with `Builder::build_landing_pad`, or by
+/// downcasting with `Instruction::try_as_landing_pad`.
+pub struct LandingPad(Instruction);
+value_subclass!(LandingPad, Instruction);
+
+impl LandingPad {
+    /// Adds a `catch` or `filter` clause. `catch` clauses are the type info
+    /// of the exceptions to catch, `filter` clauses are constant arrays.
+    pub fn add_clause(&self, clause: &Constant) {
+        unsafe { LLVMAddClause(self.into(), clause.into()) }
+    }
+
+    pub fn num_clauses(&self) -> u32 {
+        unsafe { LLVMGetNumClauses(self.into()) }
+    }
+
+    /// Returns the clause at `index`, or `None` if `index` is out of bounds.
+    pub fn get_clause(&self, index: u32) -> Option<&Constant> {
+        if index < self.num_clauses() {
+            unsafe { Some(Constant::from_raw(LLVMGetClause(self.into(), index))) }
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether this landing pad is entered even if no clause matches,
+    /// e.g. to run destructors before resuming.
+    pub fn is_cleanup(&self) -> bool {
+        unsafe { LLVMIsCleanup(self.into()) == 1 }
+    }
+
+    pub fn set_cleanup(&self, cleanup: bool) {
+        unsafe { LLVMSetCleanup(self.into(), cleanup as LLVMBool) }
+    }
+}
+
+/// A `catchswitch` instruction, which dispatches an exception to one of its
+/// `catchpad` handlers when using a Windows-style personality function.
+///
+/// `&CatchSwitch`es are created with `Builder::build_catch_switch`, or by
+/// downcasting with `Instruction::try_as_catch_switch`.
+pub struct CatchSwitch(Instruction);
+value_subclass!(CatchSwitch, Instruction);
+
+impl CatchSwitch {
+    /// Adds a handler block, which must begin with a `catchpad` whose parent
+    /// is this `catchswitch`.
+    pub fn add_handler(&self, dest: &BasicBlock) {
+        unsafe { LLVMAddHandler(self.into(), dest.into()) }
+    }
+
+    pub fn num_handlers(&self) -> u32 {
+        unsafe { LLVMGetNumHandlers(self.into()) }
+    }
+
+    /// Returns the handler blocks of this `catchswitch`, in order.
+    pub fn handlers(&self) -> Vec<&BasicBlock> {
+        let mut handlers = vec![ptr::null_mut(); self.num_handlers() as usize];
+        unsafe {
+            LLVMGetHandlers(self.into(), handlers.as_mut_ptr());
+            handlers
+                .into_iter()
+                .map(|block| BasicBlock::from_raw_block(block))
+                .collect()
+        }
+    }
+}
+
+/// A `call` or `invoke` instruction.
+///
+/// `&CallSite`s are created with `Builder::build_call` or
+/// `Builder::build_invoke`, or by downcasting with
+/// `Instruction::try_as_call_site`.
+///
+/// # Example
+///
+/// ```rust
+/// #[macro_use] extern crate llvm;
+/// use llvm::{AttributeIndex, CallConv};
+/// # fn main() {
+/// # let context = llvm::Context::new();
+/// # let module = context.create_module_with_name(llvmstr!("my module"));
+/// let i32_type = context.i32_type();
+/// let func_type = llvm::types::Function::new(i32_type, &[i32_type], false);
+/// let callee = module.add_function(func_type, llvmstr!("callee"));
+/// callee.set_call_conv(CallConv::Fast);
+///
+/// let func = module.add_function(func_type, llvmstr!("caller"));
+/// # let entry = context.append_basic_block(func, llvmstr!("entry"));
+/// # let mut builder = context.create_builder();
+/// # builder.position_at_end(entry);
+/// let call = builder.build_call(callee, &[func.get_param(0).unwrap()], llvmstr!("r"));
+/// call.set_call_conv(CallConv::Fast);
+/// call.set_tail_call(true);
+///
+/// let signext = context.create_enum_attribute("signext", 0).unwrap();
+/// call.add_attribute(AttributeIndex::Param(0), signext);
+/// assert!(call.get_enum_attribute(AttributeIndex::Param(0), "signext").is_some());
+///
+/// assert_eq!(call.called_function(), Some(callee));
+/// assert_eq!(call.call_conv(), callee.call_conv());
+/// # }
+/// ```
+pub struct CallSite(Instruction);
+value_subclass!(CallSite, Instruction);
+
+impl CallSite {
+    /// Returns the value being called, which is usually a `Function`, but
+    /// can be any function pointer.
+    pub fn called_value(&self) -> &Value {
+        unsafe { Value::from_raw(LLVMGetCalledValue(self.into())) }
+    }
+
+    /// Returns the function being called, or `None` for an indirect call.
+    pub fn called_function(&self) -> Option<&Function> {
+        let callee = unsafe { LLVMGetCalledValue(self.into()) };
+
+        if unsafe { LLVMIsAFunction(callee).is_null() } {
+            None
+        } else {
+            unsafe { Some(Function::from_raw(callee)) }
+        }
+    }
+
+    /// Returns the calling convention of this call, or `None` if it is one
+    /// that was added to LLVM after `CallConv`.
+    pub fn call_conv(&self) -> Option<CallConv> {
+        CallConv::from_id(unsafe { LLVMGetInstructionCallConv(self.into()) })
+    }
+
+    /// Sets the calling convention of this call, which should match that of
+    /// the called function.
+    pub fn set_call_conv(&self, call_conv: CallConv) {
+        unsafe { LLVMSetInstructionCallConv(self.into(), call_conv as u32) }
+    }
+
+    /// Returns whether this is a `call` marked as `tail`, or `None` for an
+    /// `invoke`.
+    pub fn is_tail_call(&self) -> Option<bool> {
+        if self.opcode() == Opcode::Call {
+            unsafe { Some(LLVMIsTailCall(self.into()) == 1) }
+        } else {
+            None
+        }
+    }
+
+    // TODO: `musttail` needs LLVMSetTailCallKind, which the C API only has
+    // since LLVM 18.
+
+    /// Marks a `call` as `tail`, a hint that it can be optimized into a jump.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is an `invoke`.
+    pub fn set_tail_call(&self, tail_call: bool) {
+        assert!(
+            self.opcode() == Opcode::Call,
+            "cannot mark {} as a tail call",
+            self
+        );
+        unsafe { LLVMSetTailCall(self.into(), tail_call as LLVMBool) }
+    }
+}
+
+attribute_fns!(
+    CallSite,
+    LLVMAddCallSiteAttribute,
+    LLVMGetCallSiteAttributeCount,
+    LLVMGetCallSiteAttributes,
+    LLVMGetCallSiteEnumAttribute,
+    LLVMGetCallSiteStringAttribute,
+    LLVMRemoveCallSiteEnumAttribute,
+    LLVMRemoveCallSiteStringAttribute
+);
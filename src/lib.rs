@@ -31,6 +31,25 @@ pub trait LLVMRef {
 }
 
 macro_rules! impl_llvm_ref {
+    // For types that borrow from a `Context`, e.g. `Builder<'ctx>`. These
+    // carry a `_marker: PhantomData` field alongside `ptr`.
+    (<$lt:lifetime> $name:ty, $ref:ty) => {
+        impl<$lt> LLVMRef for $name {
+            type LLVMRef = $ref;
+
+            unsafe fn from_raw(ptr: Self::LLVMRef) -> Self {
+                Self { ptr, _marker: ::std::marker::PhantomData }
+            }
+
+            unsafe fn as_raw(&self) -> Self::LLVMRef {
+                self.ptr
+            }
+
+            fn as_mut(&mut self) -> Self::LLVMRef {
+                self.ptr
+            }
+        }
+    };
     ($name:ty, $ref:ty) => {
         impl LLVMRef for $name {
             type LLVMRef = $ref;
@@ -58,6 +77,7 @@ pub type Result<T> = std::result::Result<T, String>;
 use llvm_sys::prelude::*;
 use llvm_sys::*;
 use llvm_sys::core::*;
+use llvm_sys::analysis::*;
 use llvm_sys::execution_engine::*;
 use llvm_sys::initialization::*;
 use llvm_sys::target::*;
@@ -65,9 +85,16 @@ use llvm_sys::target_machine::*;
 
 #[macro_use]
 mod string;
+#[macro_use]
+mod value;
+#[macro_use]
+mod attribute;
 mod context;
 pub mod types;
 mod constant;
+mod function;
+mod basic_block;
+mod instruction;
 mod builder;
 mod module;
 mod pass_manager;
@@ -77,7 +104,12 @@ mod execution_engine;
 pub use string::*;
 pub use context::*;
 pub use types::Type;
+pub use value::*;
+pub use attribute::*;
 pub use constant::*;
+pub use function::*;
+pub use basic_block::*;
+pub use instruction::*;
 pub use builder::*;
 pub use builder::IntPredicate;
 pub use module::*;
@@ -30,12 +30,51 @@ impl Module {
         unsafe { LLVMSetTarget(self.as_mut(), triple.borrow().as_ptr()) };
     }
 
-    pub fn add_function<T: Borrow<Str>>(
-        &mut self,
-        func_ty: &types::Function,
+    /// Adds a function with the given type to the module. The returned value
+    /// is owned by the module, so it can't outlive either the module or the
+    /// `Context` of `func_ty`:
+    ///
+    /// ```rust,compile_fail
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// let context = llvm::Context::new();
+    /// let func = {
+    ///     let module = context.create_module_with_name(llvmstr!("my module"));
+    ///     let func_type = llvm::types::Function::new(context.void_type(), &[], false);
+    ///     module.add_function(func_type, llvmstr!("f"))
+    /// };
+    /// # }
+    /// ```
+    pub fn add_function<'a, T: Borrow<Str>>(
+        &'a self,
+        func_ty: &'a types::Function,
         name: &T,
-    ) -> LLVMValueRef {
-        unsafe { LLVMAddFunction(self.as_mut(), name.borrow().as_ptr(), func_ty.into()) }
+    ) -> &'a Function {
+        unsafe {
+            Function::from_raw(LLVMAddFunction(
+                self.as_raw(),
+                name.borrow().as_ptr(),
+                func_ty.into(),
+            ))
+        }
+    }
+
+    /// Adds a global variable of type `ty` to the module. It is only a
+    /// declaration until its initializer is set. The returned value is owned
+    /// by the module, so it can't outlive either the module or the `Context`
+    /// of `ty`.
+    pub fn add_global<'a, T: Borrow<Str>>(
+        &'a self,
+        ty: &'a Type,
+        name: &T,
+    ) -> &'a GlobalVariable {
+        unsafe {
+            GlobalVariable::from_raw(LLVMAddGlobal(
+                self.as_raw(),
+                ty.into(),
+                name.borrow().as_ptr(),
+            ))
+        }
     }
 
     pub fn print_to_file<T: Borrow<Str>>(&self, path: &T) -> Result<()> {
@@ -51,7 +51,7 @@ impl Str {
     /// Creates a string slice pointing to the data of this `llvm::Str`, not
     /// including the null-terminator. This performs a length calculation, so
     /// this conversion isn't completely free.
-    fn as_str(&self) -> &str {
+    pub(crate) fn as_str(&self) -> &str {
         unsafe { std::str::from_utf8_unchecked(CStr::from_ptr(self.as_ptr()).to_bytes()) }
     }
 }
@@ -114,6 +114,19 @@ impl String {
     }
 }
 
+impl<'a> From<&'a str> for String {
+    /// Copies `s` into a new string owned by LLVM, e.g. to report an error
+    /// detected on the Rust side through `llvm::Result`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `s` contains a null byte.
+    fn from(s: &'a str) -> String {
+        let s = CString::new(s).expect("llvm::String can't contain null bytes");
+        unsafe { String::from_mut(LLVMCreateMessage(s.as_ptr())) }
+    }
+}
+
 impl Display for String {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         <Str as Display>::fmt(self, f)
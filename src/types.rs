@@ -10,6 +10,7 @@
 use std::fmt;
 use std::mem::transmute;
 use std::ops::Deref;
+use std::ptr;
 
 use libc::c_ulonglong;
 
@@ -22,7 +23,7 @@ use super::*;
 /// Enumeration of all the base types of the LLVM type system. Used for safe
 /// downcasting of `Type`.
 #[allow(non_camel_case_types)]
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum Kind<'a> {
     Void(&'a Void),
     Half(&'a Half),
@@ -169,7 +170,7 @@ impl Type {
         pub fn try_as_void -> Void
         pub fn try_as_half -> Half
         pub fn try_as_float -> Float
-        pub fn try_as_double -> Integer
+        pub fn try_as_double -> Double
         pub fn try_as_x86_fp80 -> X86_FP80
         pub fn try_as_fp128 -> FP128
         pub fn try_as_ppc_fp128 -> PPC_FP128
@@ -194,6 +195,70 @@ impl Type {
     pub fn pointer(&self) -> &Pointer {
         unsafe { Pointer::from_raw(LLVMPointerType(self.into(), 0)) }
     }
+
+    /// Returns the type of pointers to this type in the given address space.
+    /// The meaning of address spaces other than 0 depends on the target.
+    ///
+    /// Returns `None` for void, label, metadata and token types, which can't
+    /// be pointed to.
+    pub fn pointer_in_address_space(&self, address_space: u32) -> Option<&Pointer> {
+        match self.downcast() {
+            Kind::Void(_) | Kind::Label(_) | Kind::Metadata(_) | Kind::Token(_) => None,
+            _ => unsafe {
+                Some(Pointer::from_raw(LLVMPointerType(
+                    self.into(),
+                    address_space,
+                )))
+            },
+        }
+    }
+
+    /// Returns the type of arrays of `len` elements of this type.
+    ///
+    /// Returns `None` for void, label, metadata, function and token types,
+    /// which can't be array elements.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// let array_type = context.i8_type().array(16).unwrap();
+    /// assert_eq!(array_type.to_string(), "[16 x i8]");
+    /// assert_eq!(array_type.len(), 16);
+    /// assert!(context.void_type().array(16).is_none());
+    /// # }
+    /// ```
+    pub fn array(&self, len: u32) -> Option<&Array> {
+        match self.downcast() {
+            Kind::Void(_)
+            | Kind::Label(_)
+            | Kind::Metadata(_)
+            | Kind::Function(_)
+            | Kind::Token(_) => None,
+            _ => unsafe { Some(Array::from_raw(LLVMArrayType(self.into(), len))) },
+        }
+    }
+
+    /// Returns the type of SIMD vectors of `size` elements of this type.
+    ///
+    /// Returns `None` unless this is an integer, floating point or pointer
+    /// type and `size` is at least 1.
+    pub fn vector(&self, size: u32) -> Option<&Vector> {
+        match self.downcast() {
+            Kind::Half(_)
+            | Kind::Float(_)
+            | Kind::Double(_)
+            | Kind::X86_FP80(_)
+            | Kind::FP128(_)
+            | Kind::PPC_FP128(_)
+            | Kind::Integer(_)
+            | Kind::Pointer(_) if size > 0 => unsafe {
+                Some(Vector::from_raw(LLVMVectorType(self.into(), size)))
+            },
+            _ => None,
+        }
+    }
 }
 
 // This counts as the llvm::Type::print method from the C++ API, though the C++
@@ -259,6 +324,14 @@ macro_rules! subclass {
                 write!(f, "llvm::types::{}({})", stringify!($t), self)
             }
         }
+
+        impl PartialEq for $t {
+            fn eq(&self, other: &Self) -> bool {
+                LLVMTypeRef::from(self) == LLVMTypeRef::from(other)
+            }
+        }
+
+        impl Eq for $t {}
     }
 }
 
@@ -359,14 +432,31 @@ subclass!(UInt, Integer);
 
 impl UInt {
     pub fn constant<'a>(&'a self, val: u64) -> &'a Constant {
-        self.deref().constant(val as c_ulonglong, true)
+        self.deref().constant(val as c_ulonglong, false)
     }
 }
 
 /// Function
 ///
 /// Function types are tuples consisting of a return type and an array of
-/// parameter types.
+/// parameter types. They display as LLVM signatures, e.g. `i32 (i8*, ...)`.
+///
+/// # Example
+///
+/// ```rust
+/// # fn main() {
+/// # let context = llvm::Context::new();
+/// let i32_type: &llvm::Type = context.i32_type();
+/// let i8_ptr_type: &llvm::Type = context.i8_type().pointer();
+/// let printf_type = llvm::types::Function::new(i32_type, &[i8_ptr_type], true);
+/// assert_eq!(printf_type.to_string(), "i32 (i8*, ...)");
+///
+/// assert_eq!(printf_type.return_type(), i32_type);
+/// assert_eq!(printf_type.param_count(), 1);
+/// assert_eq!(printf_type.param_types(), vec![i8_ptr_type]);
+/// assert!(printf_type.is_var_arg());
+/// # }
+/// ```
 pub struct Function(Type);
 impl_type!(Function);
 
@@ -388,20 +478,217 @@ impl Function {
             ))
         }
     }
+
+    pub fn return_type(&self) -> &Type {
+        unsafe { Type::from_raw(LLVMGetReturnType(self.into())) }
+    }
+
+    /// Returns the number of fixed parameters, which doesn't include any
+    /// variable arguments.
+    pub fn param_count(&self) -> u32 {
+        unsafe { LLVMCountParamTypes(self.into()) }
+    }
+
+    pub fn param_types(&self) -> Vec<&Type> {
+        let mut param_types = vec![ptr::null_mut(); self.param_count() as usize];
+        unsafe {
+            LLVMGetParamTypes(self.into(), param_types.as_mut_ptr());
+            param_types.into_iter().map(|ty| Type::from_raw(ty)).collect()
+        }
+    }
+
+    /// Returns true if this function type takes variable arguments, like
+    /// `printf`.
+    pub fn is_var_arg(&self) -> bool {
+        unsafe { LLVMIsFunctionVarArg(self.into()) == 1 }
+    }
 }
 
 /// Structures
+///
+/// Literal structs are uniqued by their element types, and are created with
+/// `Context::struct_type`. Identified structs are uniqued by their name, and
+/// are created with `Context::named_struct` as opaque structs, whose body can
+/// be set later. This allows for recursive types.
+///
+/// # Example
+///
+/// ```rust
+/// #[macro_use] extern crate llvm;
+/// # fn main() {
+/// # let context = llvm::Context::new();
+/// let node = context.named_struct(llvmstr!("node"));
+/// assert!(node.is_opaque());
+///
+/// // %node = type { i32, %node* }
+/// node.set_body(&[context.i32_type(), node.pointer()], false);
+/// assert!(!node.is_opaque() && !node.is_literal());
+/// assert_eq!(node.name().unwrap().to_string(), "node");
+///
+/// let element_types: Vec<_> = node.element_types().map(|t| t.to_string()).collect();
+/// assert_eq!(element_types, ["i32", "%node*"]);
+/// # }
+/// ```
 pub struct Struct(Type);
 impl_type!(Struct);
 
+impl Struct {
+    /// Sets the element types of an opaque struct.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this isn't an opaque struct, e.g. if it is a literal struct
+    /// or its body has already been set.
+    pub fn set_body(&self, element_types: &[&Type], packed: bool) {
+        assert!(self.is_opaque(), "cannot set the body of {}", self);
+        unsafe {
+            LLVMStructSetBody(
+                self.into(),
+                transmute::<*const &Type, *mut LLVMTypeRef>(element_types.as_ptr()),
+                element_types.len() as u32,
+                packed as LLVMBool,
+            )
+        }
+    }
+
+    /// Returns true for an identified struct whose body hasn't been set.
+    pub fn is_opaque(&self) -> bool {
+        unsafe { LLVMIsOpaqueStruct(self.into()) == 1 }
+    }
+
+    /// Returns true if the elements of this struct have no padding between
+    /// them.
+    pub fn is_packed(&self) -> bool {
+        unsafe { LLVMIsPackedStruct(self.into()) == 1 }
+    }
+
+    /// Returns true if this is a literal struct rather than an identified
+    /// one.
+    ///
+    /// For an identified struct that has a body but no name, this looks up
+    /// the literal struct with the same body, which adds that type to the
+    /// context if it didn't exist yet.
+    pub fn is_literal(&self) -> bool {
+        // LLVMIsLiteralStruct isn't bound by llvm-sys. Instead, rely on named
+        // and opaque structs always being identified, and otherwise on literal
+        // structs being uniqued by their body, which identified structs never
+        // are.
+        if self.name().is_some() || self.is_opaque() {
+            return false;
+        }
+
+        let element_types: Vec<&Type> = self.element_types().collect();
+        let literal = unsafe {
+            LLVMStructTypeInContext(
+                LLVMGetTypeContext(self.into()),
+                transmute::<*const &Type, *mut LLVMTypeRef>(element_types.as_ptr()),
+                element_types.len() as u32,
+                self.is_packed() as LLVMBool,
+            )
+        };
+        literal == self.into()
+    }
+
+    /// Returns the name of an identified struct, or `None` for a literal
+    /// struct or an identified struct that was created with an empty name.
+    pub fn name(&self) -> Option<&Str> {
+        let name = unsafe { LLVMGetStructName(self.into()) };
+
+        if name.is_null() {
+            None
+        } else {
+            unsafe { Some(Str::from_ptr(name)) }
+        }
+    }
+
+    pub fn count_element_types(&self) -> u32 {
+        unsafe { LLVMCountStructElementTypes(self.into()) }
+    }
+
+    /// Returns the element type at `index`, or `None` if `index` is out of
+    /// bounds.
+    pub fn element_type(&self, index: u32) -> Option<&Type> {
+        if index < self.count_element_types() {
+            unsafe { Some(Type::from_raw(LLVMStructGetTypeAtIndex(self.into(), index))) }
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over the element types of this struct, which is
+    /// empty for an opaque struct.
+    pub fn element_types(&self) -> ElementTypeIter {
+        ElementTypeIter {
+            ty: self,
+            index: 0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ElementTypeIter<'a> {
+    ty: &'a Struct,
+    index: u32,
+}
+
+impl<'a> Iterator for ElementTypeIter<'a> {
+    type Item = &'a Type;
+
+    fn next(&mut self) -> Option<&'a Type> {
+        let ty = self.ty.element_type(self.index);
+        if ty.is_some() {
+            self.index += 1;
+        }
+        ty
+    }
+}
+
 /// Arrays
 pub struct Array(Type);
 impl_type!(Array);
 
+impl Array {
+    /// Returns the number of elements of this array type.
+    pub fn len(&self) -> u32 {
+        unsafe { LLVMGetArrayLength(self.into()) }
+    }
+
+    /// Returns true if this array type has no elements, e.g. `[0 x i8]`.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn element_type(&self) -> &Type {
+        unsafe { Type::from_raw(LLVMGetElementType(self.into())) }
+    }
+}
+
 /// Pointers
 pub struct Pointer(Type);
 impl_type!(Pointer);
 
+impl Pointer {
+    pub fn address_space(&self) -> u32 {
+        unsafe { LLVMGetPointerAddressSpace(self.into()) }
+    }
+
+    /// Returns the type that this pointer type points to.
+    pub fn pointee_type(&self) -> &Type {
+        unsafe { Type::from_raw(LLVMGetElementType(self.into())) }
+    }
+}
+
 /// SIMD 'packed' format, or other vector type
 pub struct Vector(Type);
 impl_type!(Vector);
+
+impl Vector {
+    /// Returns the number of elements of this vector type.
+    pub fn size(&self) -> u32 {
+        unsafe { LLVMGetVectorSize(self.into()) }
+    }
+
+    pub fn element_type(&self) -> &Type {
+        unsafe { Type::from_raw(LLVMGetElementType(self.into())) }
+    }
+}
@@ -1,58 +1,637 @@
-use llvm_sys::prelude::*;
-use llvm_sys::core as llvm;
+//! LLVM values.
+//!
+//! Everything that can be used as an operand in LLVM IR, e.g. constants,
+//! instructions, functions, and function arguments, is a [`Value`]. See
+//! [`Value`] for more information.
+//!
+//! [`Value`]: struct.Value.html
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::transmute;
+
+use libc::size_t;
+
 use super::*;
 
+/// Enumeration of all the kinds of LLVM values. Used for safe downcasting of
+/// `Value`.
 #[derive(Debug)]
-pub struct Value {
-    ptr: LLVMValueRef,
+pub enum ValueKind<'a> {
+    Argument(&'a Argument),
+    BasicBlock(&'a BasicBlock),
+    // Memory SSA values are only used by LLVM analyses, so they don't get
+    // their own wrappers.
+    MemoryUse(&'a Value),
+    MemoryDef(&'a Value),
+    MemoryPhi(&'a Value),
+    Function(&'a Function),
+    GlobalAlias(&'a GlobalAlias),
+    GlobalIFunc(&'a GlobalIFunc),
+    GlobalVariable(&'a GlobalVariable),
+    BlockAddress(&'a BlockAddress),
+    ConstantExpr(&'a ConstantExpr),
+    ConstantArray(&'a ConstantArray),
+    ConstantStruct(&'a ConstantStruct),
+    ConstantVector(&'a ConstantVector),
+    UndefValue(&'a UndefValue),
+    ConstantAggregateZero(&'a ConstantAggregateZero),
+    ConstantDataArray(&'a ConstantDataArray),
+    ConstantDataVector(&'a ConstantDataVector),
+    ConstantInt(&'a ConstantInt),
+    ConstantFP(&'a ConstantFP),
+    ConstantPointerNull(&'a ConstantPointerNull),
+    ConstantTokenNone(&'a ConstantTokenNone),
+    MetadataAsValue(&'a MetadataAsValue),
+    InlineAsm(&'a InlineAsm),
+    /// Instructions can be downcast further with `Instruction::downcast`.
+    Instruction(&'a Instruction),
 }
-impl_llvm_ref!(Value, LLVMValueRef);
 
-/// Represents a type that can be inserted as a const in a context
-pub trait IntoConstValue: ContextType {
-    fn gen_const(self, context: &Context) -> LLVMValueRef;
+/// The "superclass" of all LLVM values. Values can only be passed as
+/// immutable references, `&Value`s.
+///
+/// `&Value`s are produced by the methods of `Builder` and `Module`, and by
+/// constructors such as `types::Int::constant`.
+///
+/// # Ownership
+///
+/// `Value`s are owned by the `Context` (or by a `Module` within it) that
+/// created them, and live for the lifetime of that `Context`. References to
+/// them are tied to the `Context`'s lifetime, so a `&Value` cannot outlive
+/// the `Context` it belongs to.
+///
+/// Unlike `Type`s, LLVM values are mutable objects (they can be renamed, have
+/// their uses replaced, and so on). Such mutation happens in place through
+/// the `&Value`, just like it would through a `Value *` in the C++ API.
+///
+/// # Representation
+///
+/// Values are represented exactly like `Type`s: an `LLVMValueRef` is simply
+/// `transmute`d into a `&Value`, where `Value` is an opaque type whose
+/// references are only ever created by this crate. Subclasses of `Value`,
+/// such as `Constant`, use the newtype pattern and deref to their superclass,
+/// so upcasts are implicit and free. Downcasts can be performed with the
+/// [`downcast`] or [`try_as_*`] methods.
+///
+/// [`downcast`]: #method.downcast
+/// [`try_as_*`]: #method.try_as_argument
+pub struct Value(LLVMValue); // TODO: mark this as an unsized type
+
+impl<'a> From<&'a Value> for LLVMValueRef {
+    fn from(val: &'a Value) -> LLVMValueRef {
+        unsafe { transmute::<&Value, LLVMValueRef>(val) }
+    }
 }
 
-macro_rules! impl_const_value {
-    // TODO: this `tt` should be a `ty`, for some reason this causes
-    // the macro to fail, we should file a bug report
-    (UINT: $t: tt) => {
-        impl IntoConstValue for $t {
-            fn gen_const(self, context: &Context) -> LLVMValueRef {
-                unsafe {
-                    llvm::LLVMConstInt($t::get_type_in_context(context), self.into(), 0)
+macro_rules! try_as_value_fns {
+    ($(pub fn $name:ident -> $variant:tt)*) => {
+        $(
+            /// Attempt a downcast, returning `None` if the kind of `self`
+            /// doesn't match the kind requested.
+            ///
+            /// # Example
+            ///
+            /// ```rust
+            /// # fn main() {
+            /// # let context = llvm::Context::new();
+            /// let val: &llvm::Value = context.i32_type().constant(42); // upcast
+            /// assert!(val.try_as_function().is_none());
+            ///
+            /// let c = val.try_as_constant_int().unwrap();
+            /// println!("{:?}", c);
+            /// # }
+            /// ```
+            pub fn $name<'a>(&'a self) -> Option<&'a $variant> {
+                if let ValueKind::$variant(v) = self.downcast() {
+                    Some(v)
+                }
+                else {
+                    None
                 }
             }
-        }
-    };
-    (INT: $t: tt) => {
-        impl IntoConstValue for $t {
-            fn gen_const(self, context: &Context) -> LLVMValueRef {
-                unsafe {
-                    llvm::LLVMConstInt($t::get_type_in_context(context), self as u64, 1)
+        )*
+    }
+}
+
+impl Value {
+    pub unsafe fn from_raw<'a>(ptr: LLVMValueRef) -> &'a Self {
+        transmute::<LLVMValueRef, &Self>(ptr)
+    }
+
+    /// Downcast a `&Value`, returning a variant of `ValueKind` that encodes
+    /// the kind of the value and contains the result of the downcast.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// let val: &llvm::Value = context.i32_type().constant(42); // upcast
+    ///
+    /// if let llvm::ValueKind::ConstantInt(c) = val.downcast() {
+    ///     println!("{:?}", c);
+    /// }
+    /// # }
+    /// ```
+    pub fn downcast(&self) -> ValueKind {
+        unsafe {
+            match LLVMGetValueKind(self.into()) {
+                LLVMValueKind::LLVMArgumentValueKind => ValueKind::Argument(transmute(self)),
+                LLVMValueKind::LLVMBasicBlockValueKind => ValueKind::BasicBlock(transmute(self)),
+                LLVMValueKind::LLVMMemoryUseValueKind => ValueKind::MemoryUse(self),
+                LLVMValueKind::LLVMMemoryDefValueKind => ValueKind::MemoryDef(self),
+                LLVMValueKind::LLVMMemoryPhiValueKind => ValueKind::MemoryPhi(self),
+                LLVMValueKind::LLVMFunctionValueKind => ValueKind::Function(transmute(self)),
+                LLVMValueKind::LLVMGlobalAliasValueKind => ValueKind::GlobalAlias(transmute(self)),
+                LLVMValueKind::LLVMGlobalIFuncValueKind => ValueKind::GlobalIFunc(transmute(self)),
+                LLVMValueKind::LLVMGlobalVariableValueKind => {
+                    ValueKind::GlobalVariable(transmute(self))
+                }
+                LLVMValueKind::LLVMBlockAddressValueKind => {
+                    ValueKind::BlockAddress(transmute(self))
+                }
+                LLVMValueKind::LLVMConstantExprValueKind => {
+                    ValueKind::ConstantExpr(transmute(self))
+                }
+                LLVMValueKind::LLVMConstantArrayValueKind => {
+                    ValueKind::ConstantArray(transmute(self))
+                }
+                LLVMValueKind::LLVMConstantStructValueKind => {
+                    ValueKind::ConstantStruct(transmute(self))
+                }
+                LLVMValueKind::LLVMConstantVectorValueKind => {
+                    ValueKind::ConstantVector(transmute(self))
+                }
+                LLVMValueKind::LLVMUndefValueValueKind => ValueKind::UndefValue(transmute(self)),
+                LLVMValueKind::LLVMConstantAggregateZeroValueKind => {
+                    ValueKind::ConstantAggregateZero(transmute(self))
                 }
+                LLVMValueKind::LLVMConstantDataArrayValueKind => {
+                    ValueKind::ConstantDataArray(transmute(self))
+                }
+                LLVMValueKind::LLVMConstantDataVectorValueKind => {
+                    ValueKind::ConstantDataVector(transmute(self))
+                }
+                LLVMValueKind::LLVMConstantIntValueKind => ValueKind::ConstantInt(transmute(self)),
+                LLVMValueKind::LLVMConstantFPValueKind => ValueKind::ConstantFP(transmute(self)),
+                LLVMValueKind::LLVMConstantPointerNullValueKind => {
+                    ValueKind::ConstantPointerNull(transmute(self))
+                }
+                LLVMValueKind::LLVMConstantTokenNoneValueKind => {
+                    ValueKind::ConstantTokenNone(transmute(self))
+                }
+                LLVMValueKind::LLVMMetadataAsValueValueKind => {
+                    ValueKind::MetadataAsValue(transmute(self))
+                }
+                LLVMValueKind::LLVMInlineAsmValueKind => ValueKind::InlineAsm(transmute(self)),
+                LLVMValueKind::LLVMInstructionValueKind => ValueKind::Instruction(transmute(self)),
             }
         }
-    };
-    (FLOAT: $t: tt) => {
-        impl IntoConstValue for $t {
-            fn gen_const(self, context: &Context) -> LLVMValueRef {
-                unsafe {
-                    llvm::LLVMConstReal($t::get_type_in_context(context), self.into())
-                }
+    }
+
+    try_as_value_fns! {
+        pub fn try_as_argument -> Argument
+        pub fn try_as_basic_block -> BasicBlock
+        pub fn try_as_function -> Function
+        pub fn try_as_global_alias -> GlobalAlias
+        pub fn try_as_global_ifunc -> GlobalIFunc
+        pub fn try_as_global_variable -> GlobalVariable
+        pub fn try_as_block_address -> BlockAddress
+        pub fn try_as_constant_expr -> ConstantExpr
+        pub fn try_as_constant_array -> ConstantArray
+        pub fn try_as_constant_struct -> ConstantStruct
+        pub fn try_as_constant_vector -> ConstantVector
+        pub fn try_as_undef_value -> UndefValue
+        pub fn try_as_constant_aggregate_zero -> ConstantAggregateZero
+        pub fn try_as_constant_data_array -> ConstantDataArray
+        pub fn try_as_constant_data_vector -> ConstantDataVector
+        pub fn try_as_constant_int -> ConstantInt
+        pub fn try_as_constant_fp -> ConstantFP
+        pub fn try_as_constant_pointer_null -> ConstantPointerNull
+        pub fn try_as_constant_token_none -> ConstantTokenNone
+        pub fn try_as_metadata_as_value -> MetadataAsValue
+        pub fn try_as_inline_asm -> InlineAsm
+        pub fn try_as_instruction -> Instruction
+    }
+
+    /// Attempt a downcast to `Phi`; shorthand for `try_as_instruction`
+    /// followed by `Instruction::try_as_phi`.
+    pub fn try_as_phi(&self) -> Option<&Phi> {
+        self.try_as_instruction().and_then(Instruction::try_as_phi)
+    }
+
+    /// Attempt a downcast to `Switch`.
+    pub fn try_as_switch(&self) -> Option<&Switch> {
+        self.try_as_instruction().and_then(Instruction::try_as_switch)
+    }
+
+    /// Attempt a downcast to `IndirectBr`.
+    pub fn try_as_indirect_br(&self) -> Option<&IndirectBr> {
+        self.try_as_instruction().and_then(Instruction::try_as_indirect_br)
+    }
+
+    /// Attempt a downcast to `LandingPad`.
+    pub fn try_as_landing_pad(&self) -> Option<&LandingPad> {
+        self.try_as_instruction().and_then(Instruction::try_as_landing_pad)
+    }
+
+    /// Attempt a downcast to `CatchSwitch`.
+    pub fn try_as_catch_switch(&self) -> Option<&CatchSwitch> {
+        self.try_as_instruction().and_then(Instruction::try_as_catch_switch)
+    }
+
+    /// Attempt a downcast to `CallSite`, i.e. a `call` or `invoke`.
+    pub fn try_as_call_site(&self) -> Option<&CallSite> {
+        self.try_as_instruction().and_then(Instruction::try_as_call_site)
+    }
+
+    /// Attempt a downcast to `Constant`, which isn't a variant of
+    /// `ValueKind` since it has many kinds of its own.
+    pub fn try_as_constant(&self) -> Option<&Constant> {
+        unsafe {
+            if LLVMIsAConstant(self.into()).is_null() {
+                None
+            } else {
+                Some(transmute(self))
+            }
+        }
+    }
+
+    /// Attempt a downcast to `GlobalValue`, which isn't a variant of
+    /// `ValueKind` since it has many kinds of its own.
+    pub fn try_as_global_value(&self) -> Option<&GlobalValue> {
+        unsafe {
+            if LLVMIsAGlobalValue(self.into()).is_null() {
+                None
+            } else {
+                Some(transmute(self))
+            }
+        }
+    }
+
+    /// Attempt a downcast to `GlobalObject`, i.e. a function or global
+    /// variable.
+    pub fn try_as_global_object(&self) -> Option<&GlobalObject> {
+        unsafe {
+            if LLVMIsAGlobalObject(self.into()).is_null() {
+                None
+            } else {
+                Some(transmute(self))
             }
         }
     }
+
+    /// Returns the type of this value.
+    pub fn get_type(&self) -> &Type {
+        unsafe { Type::from_raw(LLVMTypeOf(self.into())) }
+    }
+
+    /// Returns the name of this value, or an empty string if it has none.
+    pub fn name(&self) -> &Str {
+        let mut len: size_t = 0;
+        unsafe { Str::from_ptr(LLVMGetValueName2(self.into(), &mut len)) }
+    }
+
+    pub fn set_name<T: Borrow<Str>>(&self, name: &T) {
+        let name = name.borrow();
+        unsafe { LLVMSetValueName2(self.into(), name.as_ptr(), name.as_str().len()) }
+    }
+
+    /// Returns an iterator over the uses of this value, i.e. the operand
+    /// slots that refer to it. Like in the C++ API, the most recently added
+    /// use comes first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i32_type = context.i32_type();
+    /// let func_type = llvm::types::Function::new(i32_type, &[i32_type], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let x = func.get_param(0).unwrap();
+    /// x.set_name(llvmstr!("x"));
+    ///
+    /// let mut builder = context.create_builder();
+    /// builder.position_at_end(context.append_basic_block(func, llvmstr!("entry")));
+    /// let sum = builder.build_add(x, x, llvmstr!("sum"));
+    /// let product = builder.build_mul(sum, x, llvmstr!("product"));
+    ///
+    /// let uses = x.uses().collect::<Vec<_>>();
+    /// assert_eq!(uses.len(), 3);
+    /// assert_eq!(uses[0].user(), product);
+    /// assert_eq!(uses[0].operand_number(), 1);
+    /// assert_eq!(uses[1].user(), sum);
+    /// assert_eq!(uses[1].operand_number(), 1);
+    /// assert_eq!(uses[2].user(), sum);
+    /// assert_eq!(uses[2].operand_number(), 0);
+    /// assert!(uses.iter().all(|u| u.used_value() == &**x));
+    ///
+    /// assert_eq!(sum.uses().count(), 1);
+    /// assert_eq!(product.uses().count(), 0);
+    /// # }
+    /// ```
+    pub fn uses(&self) -> UseIter {
+        UseIter {
+            next_use: unsafe { LLVMGetFirstUse(self.into()) },
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the values that use this value, usually
+    /// instructions. A user is yielded once for each of its operands that
+    /// refer to this value.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i32_type = context.i32_type();
+    /// let func_type = llvm::types::Function::new(i32_type, &[i32_type], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let x = func.get_param(0).unwrap();
+    /// x.set_name(llvmstr!("x"));
+    ///
+    /// let mut builder = context.create_builder();
+    /// builder.position_at_end(context.append_basic_block(func, llvmstr!("entry")));
+    /// let sum = builder.build_add(x, x, llvmstr!("sum"));
+    /// let product = builder.build_mul(sum, x, llvmstr!("product"));
+    ///
+    /// assert_eq!(x.users().collect::<Vec<_>>(), vec![product, sum, sum]);
+    /// assert_eq!(sum.users().collect::<Vec<_>>(), vec![product]);
+    /// assert!(x.has_uses());
+    /// assert!(!product.has_uses());
+    /// # }
+    /// ```
+    pub fn users(&self) -> UserIter {
+        UserIter { uses: self.uses() }
+    }
+
+    /// Return true if this value is used by anything.
+    pub fn has_uses(&self) -> bool {
+        unsafe { !LLVMGetFirstUse(self.into()).is_null() }
+    }
+
+    /// Replaces every use of this value with `new`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new` doesn't have the same type as this value.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i32_type = context.i32_type();
+    /// let func_type = llvm::types::Function::new(i32_type, &[i32_type], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let x = func.get_param(0).unwrap();
+    /// x.set_name(llvmstr!("x"));
+    ///
+    /// let mut builder = context.create_builder();
+    /// builder.position_at_end(context.append_basic_block(func, llvmstr!("entry")));
+    /// let sum = builder.build_add(x, x, llvmstr!("sum"));
+    /// let product = builder.build_mul(sum, x, llvmstr!("product"));
+    ///
+    /// sum.replace_all_uses_with(x);
+    /// assert!(!sum.has_uses());
+    /// assert_eq!(x.users().collect::<Vec<_>>(), vec![product, product, sum, sum]);
+    /// assert_eq!(product.to_string(), "  %product = mul i32 %x, %x");
+    /// # }
+    /// ```
+    ///
+    /// Replacing a value with one of another type panics:
+    ///
+    /// ```rust,should_panic
+    /// #[macro_use] extern crate llvm;
+    /// # fn main() {
+    /// # let context = llvm::Context::new();
+    /// # let module = context.create_module_with_name(llvmstr!("my module"));
+    /// let i32_type = context.i32_type();
+    /// let func_type = llvm::types::Function::new(i32_type, &[i32_type], false);
+    /// let func = module.add_function(func_type, llvmstr!("f"));
+    /// let x = func.get_param(0).unwrap();
+    /// x.set_name(llvmstr!("x"));
+    ///
+    /// let mut builder = context.create_builder();
+    /// builder.position_at_end(context.append_basic_block(func, llvmstr!("entry")));
+    /// let sum = builder.build_add(x, x, llvmstr!("sum"));
+    /// let product = builder.build_mul(sum, x, llvmstr!("product"));
+    ///
+    /// sum.replace_all_uses_with(context.i64_type().constant(0));
+    /// # }
+    /// ```
+    pub fn replace_all_uses_with(&self, new: &Value) {
+        assert!(
+            self.get_type() == new.get_type(),
+            "cannot replace uses of {} with {} of a different type",
+            self,
+            new
+        );
+        unsafe { LLVMReplaceAllUsesWith(self.into(), new.into()) }
+    }
+
+    /// Return true if this value is a constant.
+    pub fn is_constant(&self) -> bool {
+        unsafe { LLVMIsConstant(self.into()) == 1 }
+    }
+
+    /// Return true if this value is `undef`.
+    pub fn is_undef(&self) -> bool {
+        unsafe { LLVMIsUndef(self.into()) == 1 }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        unsafe {
+            write!(
+                f,
+                "{}",
+                String::from_mut(LLVMPrintValueToString(self.into()))
+            )
+        }
+    }
 }
 
-//TODO: Missing OfString and OfStringAndSize variants
-impl_const_value!(FLOAT: f64);
-impl_const_value!(FLOAT: f32);
-impl_const_value!(UINT: u8);
-impl_const_value!(UINT: u16);
-impl_const_value!(UINT: u32);
-impl_const_value!(UINT: u64);
-impl_const_value!(INT: i8);
-impl_const_value!(INT: i16);
-impl_const_value!(INT: i32);
-impl_const_value!(INT: i64);
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "llvm::Value({})", self)
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        LLVMValueRef::from(self) == LLVMValueRef::from(other)
+    }
+}
+
+impl Eq for Value {}
+
+/// An edge of the use-def graph: a single operand slot of a user (e.g. an
+/// instruction) that refers to a value. Like `Value`, `Use`s can only be
+/// passed as immutable references, `&Use`s.
+pub struct Use(LLVMUse);
+
+impl<'a> From<&'a Use> for LLVMUseRef {
+    fn from(u: &'a Use) -> LLVMUseRef {
+        unsafe { transmute::<&Use, LLVMUseRef>(u) }
+    }
+}
+
+impl Use {
+    pub unsafe fn from_raw<'a>(ptr: LLVMUseRef) -> &'a Self {
+        transmute::<LLVMUseRef, &Self>(ptr)
+    }
+
+    /// Returns the value whose operand this is.
+    pub fn user(&self) -> &Value {
+        unsafe { Value::from_raw(LLVMGetUser(self.into())) }
+    }
+
+    /// Returns the value that is used.
+    pub fn used_value(&self) -> &Value {
+        unsafe { Value::from_raw(LLVMGetUsedValue(self.into())) }
+    }
+
+    /// Returns the index of this use in the operands of its user. The C API
+    /// doesn't expose this directly, so it is found with a linear search.
+    pub fn operand_number(&self) -> u32 {
+        let user = LLVMValueRef::from(self.user());
+        let num_operands = unsafe { LLVMGetNumOperands(user) } as u32;
+
+        (0..num_operands)
+            .find(|&i| unsafe { LLVMGetOperandUse(user, i) } == LLVMUseRef::from(self))
+            .expect("a use is always one of its user's operands")
+    }
+}
+
+impl fmt::Debug for Use {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "llvm::Use({} in {})",
+            self.used_value(),
+            self.user()
+        )
+    }
+}
+
+impl PartialEq for Use {
+    fn eq(&self, other: &Self) -> bool {
+        LLVMUseRef::from(self) == LLVMUseRef::from(other)
+    }
+}
+
+impl Eq for Use {}
+
+#[derive(Debug)]
+pub struct UseIter<'a> {
+    next_use: LLVMUseRef,
+    _marker: PhantomData<&'a Use>,
+}
+
+impl<'a> Iterator for UseIter<'a> {
+    type Item = &'a Use;
+
+    fn next(&mut self) -> Option<&'a Use> {
+        if self.next_use.is_null() {
+            None
+        } else {
+            let u = unsafe { Use::from_raw(self.next_use) };
+            // Advance before the use is handed out, so that the iteration
+            // survives the caller rewriting it.
+            self.next_use = unsafe { LLVMGetNextUse(self.next_use) };
+            Some(u)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct UserIter<'a> {
+    uses: UseIter<'a>,
+}
+
+impl<'a> Iterator for UserIter<'a> {
+    type Item = &'a Value;
+
+    fn next(&mut self) -> Option<&'a Value> {
+        self.uses.next().map(Use::user)
+    }
+}
+
+/// Implements the boilerplate of a "subclass" of `Value` (or of one of its
+/// subclasses), see `types::Type` for how this works.
+macro_rules! value_subclass {
+    ($t:ident, $super:ty) => {
+        impl ::std::ops::Deref for $t {
+            type Target = $super;
+
+            fn deref(&self) -> &Self::Target {
+                unsafe { ::std::mem::transmute::<&Self, &Self::Target>(self) }
+            }
+        }
+
+        // This would not be needed if the compiler could infer that the
+        // From<&Value> for LLVMValueRef above worked on &SubValue.
+        impl<'a> From<&'a $t> for LLVMValueRef {
+            fn from(val: &'a $t) -> LLVMValueRef {
+                unsafe { ::std::mem::transmute::<&$t, LLVMValueRef>(val) }
+            }
+        }
+
+        impl $t {
+            pub unsafe fn from_raw<'a>(ptr: LLVMValueRef) -> &'a Self {
+                ::std::mem::transmute::<LLVMValueRef, &Self>(ptr)
+            }
+        }
+
+        impl ::std::fmt::Display for $t {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                ::std::fmt::Display::fmt(::std::ops::Deref::deref(self), f)
+            }
+        }
+
+        impl ::std::fmt::Debug for $t {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                write!(f, "llvm::{}({})", stringify!($t), self)
+            }
+        }
+
+        impl PartialEq for $t {
+            fn eq(&self, other: &Self) -> bool {
+                LLVMValueRef::from(self) == LLVMValueRef::from(other)
+            }
+        }
+
+        impl Eq for $t {}
+    }
+}
+
+/// Formal arguments of a `Function`
+pub struct Argument(Value);
+value_subclass!(Argument, Value);
+
+impl Argument {
+    /// Returns the function that this is an argument of.
+    pub fn parent(&self) -> &Function {
+        unsafe { Function::from_raw(LLVMGetParamParent(self.into())) }
+    }
+}
+
+/// Metadata wrapped so it can be used as an operand, e.g. of intrinsics
+pub struct MetadataAsValue(Value);
+value_subclass!(MetadataAsValue, Value);
+
+/// Inline assembler expressions
+pub struct InlineAsm(Value);
+value_subclass!(InlineAsm, Value);
@@ -0,0 +1,165 @@
+#[macro_use]
+extern crate llvm;
+extern crate llvm_sys;
+
+use std::mem;
+
+use llvm::types::Function;
+use llvm::{Constant, Context, IntPredicate, Module};
+use llvm_sys::core::LLVMConstPointerNull;
+
+// Builds a module in which `run(throw, cleanups)` invokes `rethrow`, which
+// invokes `thrower`. If `throw` is non-zero, `thrower` throws the C++ int 42,
+// `rethrow` counts the exception passing through its cleanup landing pad in
+// `*cleanups` and resumes it, and `run` catches it and returns it. Otherwise
+// `run` returns 0.
+fn build_module(context: &Context) -> Module {
+    let module = context.create_module_with_name(llvmstr!("exceptions"));
+    {
+        // The functions borrow the module, so they have to go out of scope
+        // before the module can be returned.
+        let void_type = context.void_type();
+        let i32_type = context.i32_type();
+        let i8_ptr_type = context.i8_type().pointer();
+        let i32_ptr_type = i32_type.pointer();
+        let exn_type = context.struct_type(&[i8_ptr_type, i32_type], false);
+
+        let personality = module.add_function(
+            Function::new(i32_type, &[], true),
+            llvmstr!("__gxx_personality_v0"),
+        );
+        let allocate_exception = module.add_function(
+            Function::new(i8_ptr_type, &[context.i64_type()], false),
+            llvmstr!("__cxa_allocate_exception"),
+        );
+        let throw = module.add_function(
+            Function::new(void_type, &[i8_ptr_type, i8_ptr_type, i8_ptr_type], false),
+            llvmstr!("__cxa_throw"),
+        );
+        let begin_catch = module.add_function(
+            Function::new(i8_ptr_type, &[i8_ptr_type], false),
+            llvmstr!("__cxa_begin_catch"),
+        );
+        let end_catch = module.add_function(
+            Function::new(void_type, &[], false),
+            llvmstr!("__cxa_end_catch"),
+        );
+        // The type info of `int`.
+        let int_type_info = module.add_global(i8_ptr_type, llvmstr!("_ZTIi"));
+        let null: &Constant =
+            unsafe { Constant::from_raw(LLVMConstPointerNull(i8_ptr_type.into())) };
+
+        let mut builder = context.create_builder();
+
+        let thrower = module.add_function(
+            Function::new(void_type, &[i32_type], false),
+            llvmstr!("thrower"),
+        );
+        let entry = context.append_basic_block(thrower, llvmstr!("entry"));
+        let throw_block = context.append_basic_block(thrower, llvmstr!("throw"));
+        let done = context.append_basic_block(thrower, llvmstr!("done"));
+        builder.position_at_end(entry);
+        let zero = i32_type.constant(0);
+        let should_throw = builder.build_icmp(
+            IntPredicate::Ne,
+            thrower.get_param(0).unwrap(),
+            zero,
+            llvmstr!("should_throw"),
+        );
+        builder.build_cond_br(should_throw, throw_block, done);
+        builder.position_at_end(throw_block);
+        let exn = builder.build_call(
+            allocate_exception,
+            &[context.i64_type().constant(4)],
+            llvmstr!("exn"),
+        );
+        let payload = builder.build_bit_cast(exn, i32_ptr_type, llvmstr!("payload"));
+        builder.build_store(i32_type.constant(42), payload);
+        let type_info = builder.build_pointer_cast(int_type_info, i8_ptr_type, llvmstr!(""));
+        builder.build_call(throw, &[exn, type_info, null], llvmstr!(""));
+        builder.build_unreachable();
+        builder.position_at_end(done);
+        builder.build_ret_void();
+
+        let rethrow = module.add_function(
+            Function::new(void_type, &[i32_type, i32_ptr_type], false),
+            llvmstr!("rethrow"),
+        );
+        rethrow.set_personality_fn(personality);
+        let entry = context.append_basic_block(rethrow, llvmstr!("entry"));
+        let done = context.append_basic_block(rethrow, llvmstr!("done"));
+        let cleanup = context.append_basic_block(rethrow, llvmstr!("cleanup"));
+        builder.position_at_end(entry);
+        builder.build_invoke(
+            thrower,
+            &[rethrow.get_param(0).unwrap()],
+            done,
+            cleanup,
+            llvmstr!(""),
+        );
+        builder.position_at_end(done);
+        builder.build_ret_void();
+        builder.position_at_end(cleanup);
+        let lpad = builder.build_landing_pad(exn_type, personality, 0, llvmstr!("lpad"));
+        lpad.set_cleanup(true);
+        let cleanups = rethrow.get_param(1).unwrap();
+        let count = builder.build_load(cleanups, llvmstr!("count"));
+        let count = builder.build_add(count, i32_type.constant(1), llvmstr!("count"));
+        builder.build_store(count, cleanups);
+        builder.build_resume(lpad);
+
+        let run = module.add_function(
+            Function::new(i32_type, &[i32_type, i32_ptr_type], false),
+            llvmstr!("run"),
+        );
+        run.set_personality_fn(personality);
+        let entry = context.append_basic_block(run, llvmstr!("entry"));
+        let done = context.append_basic_block(run, llvmstr!("done"));
+        let catch = context.append_basic_block(run, llvmstr!("catch"));
+        builder.position_at_end(entry);
+        builder.build_invoke(
+            rethrow,
+            &[run.get_param(0).unwrap(), run.get_param(1).unwrap()],
+            done,
+            catch,
+            llvmstr!(""),
+        );
+        builder.position_at_end(done);
+        builder.build_ret(zero);
+        builder.position_at_end(catch);
+        let lpad = builder.build_landing_pad(exn_type, personality, 1, llvmstr!("lpad"));
+        // A null type info catches everything.
+        lpad.add_clause(null);
+        let exn = builder.build_extract_value(lpad, 0, llvmstr!("exn"));
+        let payload = builder.build_call(begin_catch, &[exn], llvmstr!("payload"));
+        let payload = builder.build_bit_cast(payload, i32_ptr_type, llvmstr!("payload"));
+        let val = builder.build_load(payload, llvmstr!("val"));
+        builder.build_call(end_catch, &[], llvmstr!(""));
+        builder.build_ret(val);
+
+        assert!(thrower.verify());
+        assert!(rethrow.verify());
+        assert!(run.verify());
+    }
+    module
+}
+
+#[test]
+fn invoke_landing_pad_and_resume() {
+    let context = Context::new();
+    let module = build_module(&context);
+
+    llvm::link_in_mcjit();
+    llvm::initialize_native_target();
+    llvm::initialize_native_asm_printer();
+
+    let ee = llvm::ExecutionEngine::create_for_module(module).unwrap();
+    let addr = ee.get_function_address(llvmstr!("run")).unwrap();
+    let run: extern "C" fn(i32, *mut i32) -> i32 = unsafe { mem::transmute(addr) };
+
+    let mut cleanups = 0;
+    assert_eq!(run(0, &mut cleanups), 0);
+    assert_eq!(cleanups, 0);
+    assert_eq!(run(1, &mut cleanups), 42);
+    assert_eq!(cleanups, 1);
+}
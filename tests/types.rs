@@ -0,0 +1,195 @@
+#[macro_use]
+extern crate llvm;
+extern crate llvm_sys;
+
+use llvm::types::{self, Kind};
+use llvm::{Constant, Context, Type};
+use llvm_sys::core::{LLVMConstIntGetSExtValue, LLVMConstIntGetZExtValue};
+
+fn zext(constant: &Constant) -> u64 {
+    unsafe { LLVMConstIntGetZExtValue(constant.into()) }
+}
+
+fn sext(constant: &Constant) -> i64 {
+    unsafe { LLVMConstIntGetSExtValue(constant.into()) }
+}
+
+fn uint_type(context: &Context, num_bits: u32) -> &types::UInt {
+    unsafe { types::UInt::from_raw(context.integer_type(num_bits).into()) }
+}
+
+#[test]
+fn downcast_every_kind() {
+    let context = Context::new();
+    let i32_type = context.i32_type();
+
+    let void_type = context.void_type();
+    assert_eq!(void_type.downcast(), Kind::Void(void_type));
+    let half_type = context.half_type();
+    assert_eq!(half_type.downcast(), Kind::Half(half_type));
+    let float_type = context.float_type();
+    assert_eq!(float_type.downcast(), Kind::Float(float_type));
+    let double_type = context.double_type();
+    assert_eq!(double_type.downcast(), Kind::Double(double_type));
+    let x86_fp80_type = context.x86_fp80_type();
+    assert_eq!(x86_fp80_type.downcast(), Kind::X86_FP80(x86_fp80_type));
+    let fp128_type = context.fp128_type();
+    assert_eq!(fp128_type.downcast(), Kind::FP128(fp128_type));
+    let ppc_fp128_type = context.ppc_fp128_type();
+    assert_eq!(ppc_fp128_type.downcast(), Kind::PPC_FP128(ppc_fp128_type));
+    let label_type = context.label_type();
+    assert_eq!(label_type.downcast(), Kind::Label(label_type));
+    assert_eq!(i32_type.downcast(), Kind::Integer(i32_type));
+    let func_type = types::Function::new(i32_type, &[i32_type], false);
+    assert_eq!(func_type.downcast(), Kind::Function(func_type));
+    let struct_type = context.struct_type(&[i32_type, double_type], false);
+    assert_eq!(struct_type.downcast(), Kind::Struct(struct_type));
+    let array_type = i32_type.array(4).unwrap();
+    assert_eq!(array_type.downcast(), Kind::Array(array_type));
+    let pointer_type = i32_type.pointer();
+    assert_eq!(pointer_type.downcast(), Kind::Pointer(pointer_type));
+    let vector_type = i32_type.vector(4).unwrap();
+    assert_eq!(vector_type.downcast(), Kind::Vector(vector_type));
+    let metadata_type = context.metadata_type();
+    assert_eq!(metadata_type.downcast(), Kind::Metadata(metadata_type));
+    let x86_mmx_type = context.x86_mmx_type();
+    assert_eq!(x86_mmx_type.downcast(), Kind::X86_MMX(x86_mmx_type));
+    let token_type = context.token_type();
+    assert_eq!(token_type.downcast(), Kind::Token(token_type));
+}
+
+#[test]
+fn try_as_matches_kind() {
+    let context = Context::new();
+    let double_type = context.double_type();
+    let i32_type = context.i32_type();
+
+    assert_eq!(double_type.try_as_double(), Some(double_type));
+    assert_eq!(double_type.try_as_integer(), None);
+    assert_eq!(i32_type.try_as_integer(), Some(&**i32_type));
+    assert_eq!(i32_type.try_as_double(), None);
+    assert_eq!(context.token_type().try_as_token(), Some(context.token_type()));
+    assert_eq!(i32_type.pointer().try_as_pointer(), Some(i32_type.pointer()));
+}
+
+#[test]
+fn global_context_types() {
+    // This is the only test that uses the global context.
+    unsafe {
+        {
+            let context = Context::global();
+            let fp128_type = context.fp128_type();
+            assert_eq!(fp128_type.downcast(), Kind::FP128(fp128_type));
+            assert_eq!(context.i8_type(), Context::global().i8_type());
+        }
+
+        // Dropping the handle above must not have disposed of the context.
+        assert_eq!(Context::global().i8_type().to_string(), "i8");
+    }
+}
+
+#[test]
+fn types_are_uniqued_per_context() {
+    let context = Context::new();
+    let other_context = Context::new();
+
+    assert_eq!(context.i32_type(), context.integer_type(32));
+    assert_ne!(context.i32_type(), context.i64_type());
+    assert_ne!(context.i32_type(), other_context.i32_type());
+    assert_eq!(context.float_type().pointer(), context.float_type().pointer());
+    assert_ne!(
+        Some(context.float_type().pointer()),
+        context.float_type().pointer_in_address_space(1)
+    );
+    assert_eq!(context.i8_type().array(3), context.i8_type().array(3));
+    assert!(context.i8_type().array(3).is_some());
+    assert_ne!(context.i8_type().array(3), context.i8_type().array(4));
+
+    let i32_type: &Type = context.i32_type();
+    let literal = context.struct_type(&[i32_type], false);
+    assert_eq!(literal, context.struct_type(&[i32_type], false));
+    assert_ne!(literal, context.struct_type(&[i32_type], true));
+
+    let named = context.named_struct(llvmstr!("pair"));
+    named.set_body(&[i32_type], false);
+    assert_ne!(named, literal);
+}
+
+#[test]
+fn invalid_derived_types() {
+    let context = Context::new();
+    let i32_type = context.i32_type();
+    let func_type = types::Function::new(i32_type, &[], false);
+
+    assert!(context.void_type().pointer_in_address_space(0).is_none());
+    assert!(context.label_type().pointer_in_address_space(0).is_none());
+    assert!(func_type.pointer_in_address_space(0).is_some());
+
+    assert!(context.void_type().array(2).is_none());
+    assert!(func_type.array(2).is_none());
+    assert!(context.token_type().array(2).is_none());
+    assert!(context.struct_type(&[], false).array(0).unwrap().is_empty());
+    assert!(!i32_type.array(1).unwrap().is_empty());
+
+    assert!(i32_type.vector(0).is_none());
+    assert!(context.label_type().vector(4).is_none());
+    assert!(context.struct_type(&[], false).vector(4).is_none());
+    assert!(context.double_type().vector(2).is_some());
+    assert!(i32_type.pointer().vector(2).is_some());
+}
+
+#[test]
+fn int_constants_round_trip() {
+    let context = Context::new();
+    let i64_type = context.i64_type();
+    let i8_type = context.i8_type();
+
+    assert_eq!(sext(i64_type.constant(i64::MIN)), i64::MIN);
+    assert_eq!(sext(i64_type.constant(-1)), -1);
+    assert_eq!(zext(i64_type.constant(-1)), u64::MAX);
+    assert_eq!(sext(i8_type.constant(-1)), -1);
+    assert_eq!(zext(i8_type.constant(-1)), 0xff);
+    assert_eq!(sext(i8_type.constant(127)), 127);
+}
+
+#[test]
+fn uint_constants_round_trip() {
+    let context = Context::new();
+    let u64_type = uint_type(&context, 64);
+    let u32_type = uint_type(&context, 32);
+
+    assert_eq!(zext(u64_type.constant(u64::MAX)), u64::MAX);
+    assert_eq!(zext(u64_type.constant(1 << 63)), 1 << 63);
+    assert_eq!(sext(u64_type.constant(1 << 63)), i64::MIN);
+    assert_eq!(zext(u32_type.constant(0xffff_ffff)), 0xffff_ffff);
+    assert_eq!(sext(u32_type.constant(0xffff_ffff)), -1);
+    assert_eq!(zext(u32_type.constant(0)), 0);
+
+    // Too wide for LLVMConstIntGetZExtValue, but not zero extending here would
+    // make the constant -1.
+    let u128_type = uint_type(&context, 128);
+    let constant = u128_type.constant(u64::MAX);
+    assert_eq!(constant.to_string(), "i128 18446744073709551615");
+}
+
+#[test]
+fn struct_literalness() {
+    let context = Context::new();
+    let i32_type: &Type = context.i32_type();
+
+    assert!(context.struct_type(&[], false).is_literal());
+    assert!(context.struct_type(&[i32_type], false).is_literal());
+    assert!(context.struct_type(&[i32_type], true).is_literal());
+
+    let named = context.named_struct(llvmstr!("wrapper"));
+    assert!(!named.is_literal());
+    named.set_body(&[i32_type], false);
+    assert!(!named.is_literal());
+    assert_eq!(named.name().unwrap().to_string(), "wrapper");
+
+    let unnamed = context.named_struct(llvmstr!(""));
+    assert!(unnamed.name().is_none());
+    assert!(!unnamed.is_literal());
+    unnamed.set_body(&[i32_type, i32_type], false);
+    assert!(!unnamed.is_literal());
+}